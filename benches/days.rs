@@ -0,0 +1,202 @@
+//! Times the generator and both parts of every day on the real inputs.
+//!
+//! `cargo bench [-- <day>...]` prints a table and writes a JSON report to
+//! `target/bench-report.json`, or to `BENCH_REPORT` if set. `BENCH_WARMUP` and
+//! `BENCH_ITERATIONS` override the number of warm-up and measured iterations.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env, fs,
+    hint::black_box,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use advent_of_code_2022::{
+    day1::Day1, day2::Day2, day3::Day3, day4::Day4, day5::Day5, day6::Day6, day7::Day7, day8::Day8,
+    solution::Solution,
+};
+
+/// Forwards to the system allocator while tracking live and peak bytes.
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+struct Config {
+    warmup: usize,
+    iterations: usize,
+}
+
+struct Stats {
+    iterations: usize,
+    min: Duration,
+    median: Duration,
+    p90: Duration,
+    p99: Duration,
+    max: Duration,
+    /// Largest number of bytes allocated on top of what was live before the
+    /// measured call.
+    peak_bytes: usize,
+}
+
+fn measure<T>(config: &Config, mut f: impl FnMut() -> T) -> Stats {
+    for _ in 0..config.warmup {
+        black_box(f());
+    }
+
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    black_box(f());
+    let peak_bytes = PEAK.load(Ordering::Relaxed) - baseline;
+
+    let mut samples = (0..config.iterations.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect::<Vec<_>>();
+    samples.sort_unstable();
+    let percentile = |p: usize| samples[(samples.len() - 1) * p / 100];
+    Stats {
+        iterations: samples.len(),
+        min: samples[0],
+        median: percentile(50),
+        p90: percentile(90),
+        p99: percentile(99),
+        max: samples[samples.len() - 1],
+        peak_bytes,
+    }
+}
+
+struct DayReport {
+    day: u8,
+    generator: Stats,
+    part1: Stats,
+    part2: Stats,
+}
+
+fn bench<S: Solution>(config: &Config, input_dir: &Path) -> DayReport {
+    let input = fs::read_to_string(input_dir.join(format!("day{}.txt", S::DAY)))
+        .unwrap_or_else(|e| panic!("cannot read input of day {}: {}", S::DAY, e));
+    let generator = measure(config, || S::parse(&input).unwrap());
+    let parsed = S::parse(&input).unwrap();
+    DayReport {
+        day: S::DAY,
+        generator,
+        part1: measure(config, || S::part1(&parsed)),
+        part2: measure(config, || S::part2(&parsed)),
+    }
+}
+
+type Bench = fn(&Config, &Path) -> DayReport;
+
+fn stats_json(stats: &Stats) -> String {
+    format!(
+        r#"{{"iterations":{},"min_ns":{},"median_ns":{},"p90_ns":{},"p99_ns":{},"max_ns":{},"peak_bytes":{}}}"#,
+        stats.iterations,
+        stats.min.as_nanos(),
+        stats.median.as_nanos(),
+        stats.p90.as_nanos(),
+        stats.p99.as_nanos(),
+        stats.max.as_nanos(),
+        stats.peak_bytes
+    )
+}
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn main() {
+    let config = Config {
+        warmup: env_or("BENCH_WARMUP", 10),
+        iterations: env_or("BENCH_ITERATIONS", 100),
+    };
+    // `cargo bench` passes `--bench`, anything else selects days
+    let days = env::args()
+        .skip(1)
+        .filter_map(|a| a.parse::<u8>().ok())
+        .collect::<Vec<_>>();
+    let selected = |day: u8| days.is_empty() || days.contains(&day);
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let input_dir = root.join("input/2022");
+    let benches: [(u8, Bench); 8] = [
+        (Day1::DAY, bench::<Day1>),
+        (Day2::DAY, bench::<Day2>),
+        (Day3::DAY, bench::<Day3>),
+        (Day4::DAY, bench::<Day4>),
+        (Day5::DAY, bench::<Day5>),
+        (Day6::DAY, bench::<Day6>),
+        (Day7::DAY, bench::<Day7>),
+        (Day8::DAY, bench::<Day8>),
+    ];
+
+    println!(
+        "{:<4} {:<9} {:>12} {:>12} {:>12} {:>12}",
+        "day", "step", "median", "p90", "p99", "peak bytes"
+    );
+    let mut reports = vec![];
+    for (day, bench) in benches {
+        if !selected(day) {
+            continue;
+        }
+        let report = bench(&config, &input_dir);
+        for (step, stats) in [
+            ("generator", &report.generator),
+            ("part1", &report.part1),
+            ("part2", &report.part2),
+        ] {
+            println!(
+                "{:<4} {:<9} {:>12.2?} {:>12.2?} {:>12.2?} {:>12}",
+                report.day, step, stats.median, stats.p90, stats.p99, stats.peak_bytes
+            );
+        }
+        reports.push(report);
+    }
+
+    let json = reports
+        .iter()
+        .map(|r| {
+            format!(
+                r#"{{"day":{},"generator":{},"part1":{},"part2":{}}}"#,
+                r.day,
+                stats_json(&r.generator),
+                stats_json(&r.part1),
+                stats_json(&r.part2)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let path = env::var_os("BENCH_REPORT")
+        .map(Into::into)
+        .unwrap_or_else(|| root.join("target/bench-report.json"));
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, format!("[{}]\n", json)).unwrap();
+    println!("report written to {}", path.display());
+}
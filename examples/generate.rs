@@ -0,0 +1,61 @@
+//! Writes a generated stress-test input to stdout and its known answers to
+//! stderr, e.g. `cargo run --release --example generate -- 6 42 1000000000`.
+
+use std::{
+    env,
+    io::{self, BufWriter, Write},
+    process::ExitCode,
+};
+
+use advent_of_code_2022::generators;
+
+const USAGE: &str = "usage: generate <day> <seed> <scale>
+
+  day 1: <scale> elves
+  day 5: <scale> stacks and 10 * <scale> moves
+  day 6: a <scale> characters long signal, at least 18
+  day 7: <scale> directories
+  day 8: a <scale> by <scale> forest";
+
+fn main() -> ExitCode {
+    let args = env::args()
+        .skip(1)
+        .map(|a| a.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>();
+    let (day, seed, scale) = match args.as_deref() {
+        Ok(&[day, seed, scale]) => (day, seed as u64, scale),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let known = match day {
+        1 => generators::day1(&mut out, seed, scale, 20),
+        5 => generators::day5(&mut out, seed, scale.max(2), 50, 10 * scale),
+        6 => {
+            let (packet, message) = generators::day6_markers(scale.max(18));
+            generators::day6(&mut out, seed, scale.max(18), packet, message)
+        }
+        7 => generators::day7(&mut out, seed, scale, 10),
+        8 => generators::day8(&mut out, seed, scale, scale),
+        _ => {
+            eprintln!("no generator for day {}\n\n{}", day, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let known = known.and_then(|k| out.flush().map(|_| k));
+    match known {
+        Ok(known) => {
+            let show = |a: Option<String>| a.unwrap_or_else(|| String::from("unknown"));
+            eprintln!("part 1: {}", show(known.part1));
+            eprintln!("part 2: {}", show(known.part2));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
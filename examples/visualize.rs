@@ -0,0 +1,92 @@
+//! Renders a puzzle input to SVG files in a directory, e.g.
+//! `cargo run --example visualize -- 5 input/2022/day5.txt frames/`.
+
+use std::{env, fs, path::Path, process::ExitCode};
+
+use advent_of_code_2022::{
+    day5::Day5,
+    day7::Day7,
+    day8::Forest,
+    solution::Solution,
+    visualize::{self, ForestView},
+};
+
+const USAGE: &str = "usage: visualize <day> <input> <out-dir>
+
+  day 5: the stacks after each move, for both cranes (day5-part<N>-*.svg)
+  day 6: both markers (day6.svg) and the sliding windows (day6-part<N>-*.svg)
+  day 7: the directory tree sized by usage (day7.svg)
+  day 8: visibility (day8-visibility.svg) and scenic scores (day8-scenic.svg)";
+
+fn render(day: u8, input: &str, out: &Path) -> Result<usize, String> {
+    let mut written = 0;
+    let mut write = |name: &str, svg: String| {
+        fs::create_dir_all(out)
+            .and_then(|_| fs::write(out.join(format!("{}.svg", name)), svg))
+            .map(|_| written += 1)
+    };
+    match day {
+        5 => {
+            let ship = Day5::parse(input).map_err(|e| e.to_string())?;
+            for (part, sticky) in [(1, false), (2, true)] {
+                let frames = visualize::ship_frames(&ship, sticky);
+                visualize::write_frames(out, &format!("day5-part{}", part), &frames)
+                    .map_err(|e| e.to_string())?;
+                written += frames.len();
+            }
+        }
+        6 => {
+            let signal = input.trim();
+            write("day6", visualize::markers(signal, &[4, 14], 64)).map_err(|e| e.to_string())?;
+            for (part, length) in [(1, 4), (2, 14)] {
+                let frames = visualize::marker_frames(signal, length);
+                visualize::write_frames(out, &format!("day6-part{}", part), &frames)
+                    .map_err(|e| e.to_string())?;
+                written += frames.len();
+            }
+        }
+        7 => {
+            let fs = Day7::parse(input).map_err(|e| e.to_string())?;
+            write("day7", visualize::file_system(&fs)).map_err(|e| e.to_string())?;
+        }
+        8 => {
+            let forest = Forest::parse(input).map_err(|e| e.to_string())?;
+            write("day8-visibility", visualize::forest(&forest, ForestView::Visibility))
+                .map_err(|e| e.to_string())?;
+            write("day8-scenic", visualize::forest(&forest, ForestView::ScenicScore))
+                .map_err(|e| e.to_string())?;
+        }
+        _ => return Err(format!("nothing to render for day {}", day)),
+    }
+    Ok(written)
+}
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let (day, input, out) = match args.as_slice() {
+        [day, input, out] => match day.parse::<u8>() {
+            Ok(day) => (day, input, Path::new(out)),
+            Err(_) => {
+                eprintln!("{}", USAGE);
+                return ExitCode::from(2);
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let result = fs::read_to_string(input)
+        .map_err(|e| format!("cannot read {}: {}", input, e))
+        .and_then(|input| render(day, &input, out));
+    match result {
+        Ok(n) => {
+            eprintln!("wrote {} files to {}", n, out.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,21 @@
+#![no_main]
+
+use advent_of_code_2022::{
+    day1::{self, Day1},
+    solution::Solution,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(generated) = day1::input_generator(input) {
+        day1::solve_part1(&generated);
+        day1::solve_part2(&generated);
+    }
+    if let Ok(parsed) = Day1::parse(input) {
+        Day1::part1(&parsed);
+        Day1::part2(&parsed);
+    }
+});
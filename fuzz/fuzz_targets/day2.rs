@@ -0,0 +1,21 @@
+#![no_main]
+
+use advent_of_code_2022::{
+    day2::{self, Day2},
+    solution::Solution,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(generated) = day2::parse_input(input) {
+        day2::solve_part1(&generated);
+        day2::solve_part2(&generated);
+    }
+    if let Ok(parsed) = Day2::parse(input) {
+        Day2::part1(&parsed);
+        Day2::part2(&parsed);
+    }
+});
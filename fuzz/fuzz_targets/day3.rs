@@ -0,0 +1,21 @@
+#![no_main]
+
+use advent_of_code_2022::{
+    day3::{self, Day3},
+    solution::Solution,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(generated) = day3::input_generator(input) {
+        day3::solve_part1(&generated);
+        day3::solve_part2(&generated);
+    }
+    if let Ok(parsed) = Day3::parse(input) {
+        Day3::part1(&parsed);
+        Day3::part2(&parsed);
+    }
+});
@@ -0,0 +1,21 @@
+#![no_main]
+
+use advent_of_code_2022::{
+    day4::{self, Day4},
+    solution::Solution,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(generated) = day4::input_generator(input) {
+        day4::solve_part1(&generated);
+        day4::solve_part2(&generated);
+    }
+    if let Ok(parsed) = Day4::parse(input) {
+        Day4::part1(&parsed);
+        Day4::part2(&parsed);
+    }
+});
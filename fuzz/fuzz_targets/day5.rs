@@ -0,0 +1,21 @@
+#![no_main]
+
+use advent_of_code_2022::{
+    day5::{self, Day5},
+    solution::Solution,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(generated) = day5::input_generator(input) {
+        day5::solve_part1(&generated);
+        day5::solve_part2(&generated);
+    }
+    if let Ok(parsed) = Day5::parse(input) {
+        Day5::part1(&parsed);
+        Day5::part2(&parsed);
+    }
+});
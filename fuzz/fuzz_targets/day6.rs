@@ -0,0 +1,21 @@
+#![no_main]
+
+use advent_of_code_2022::{
+    day6::{self, Day6},
+    solution::Solution,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(generated) = day6::input_generator(input) {
+        day6::solve_day6_part1(&generated);
+        day6::solve_day6_part2(&generated);
+    }
+    if let Ok(parsed) = Day6::parse(input) {
+        Day6::part1(&parsed);
+        Day6::part2(&parsed);
+    }
+});
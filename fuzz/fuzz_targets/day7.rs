@@ -0,0 +1,22 @@
+#![no_main]
+
+use advent_of_code_2022::{
+    day7::{self, Day7, FileSystem, Relisting},
+    solution::Solution,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(generated) = day7::input_generator(input) {
+        day7::solve_part1(&generated);
+        day7::solve_part2(&generated);
+    }
+    if let Ok(parsed) = Day7::parse(input) {
+        Day7::part1(&parsed);
+        Day7::part2(&parsed);
+    }
+    let _ = FileSystem::parse_with(input, Relisting::Reject);
+});
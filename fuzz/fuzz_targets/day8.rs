@@ -0,0 +1,21 @@
+#![no_main]
+
+use advent_of_code_2022::{
+    day8::{self, Day8},
+    solution::Solution,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(generated) = day8::input_generator(input) {
+        day8::solve_part1(&generated);
+        day8::solve_part2(&generated);
+    }
+    if let Ok(parsed) = Day8::parse(input) {
+        Day8::part1(&parsed);
+        Day8::part2(&parsed);
+    }
+});
@@ -0,0 +1,324 @@
+use std::{error::Error, fmt, str::FromStr};
+
+/// Error returned by every puzzle parser, pointing at the offending input.
+/// Lines and columns both start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    /// Number of columns the error spans, at least 1.
+    pub width: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// One line of puzzle input, without its line ending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+/// A slice of a [`Line`], remembering where it starts so errors can point
+/// at it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field<'a> {
+    pub line: usize,
+    pub column: usize,
+    pub text: &'a str,
+}
+
+/// Splits `input` into numbered lines, accepting both `\n` and `\r\n`.
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, text)| Line::new(i + 1, text))
+}
+
+/// Splits `input` into blocks separated by blank lines. Leading, trailing and
+/// repeated blank lines never produce empty blocks.
+pub fn blocks(input: &str) -> Vec<Vec<Line<'_>>> {
+    let mut ret = vec![];
+    let mut block = vec![];
+    for line in lines(input) {
+        if line.is_blank() {
+            if !block.is_empty() {
+                ret.push(std::mem::take(&mut block));
+            }
+        } else {
+            block.push(line);
+        }
+    }
+    if !block.is_empty() {
+        ret.push(block);
+    }
+    ret
+}
+
+impl<'a> Line<'a> {
+    /// Wraps `text`, dropping a trailing `\n` or `\r\n` if present.
+    pub fn new(number: usize, text: &'a str) -> Self {
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        Self { number, text }
+    }
+
+    pub fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    pub fn error(&self, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.number,
+            column,
+            width: 1,
+            message: message.into(),
+        }
+    }
+
+    /// Turns `sub`, which must be a slice of this line, into a [`Field`].
+    pub fn field(&self, sub: &'a str) -> Field<'a> {
+        let offset = sub.as_ptr() as usize - self.text.as_ptr() as usize;
+        debug_assert!(offset + sub.len() <= self.text.len());
+        Field {
+            line: self.number,
+            column: offset + 1,
+            text: sub,
+        }
+    }
+
+    /// The line without its surrounding whitespace.
+    pub fn trimmed(&self) -> Field<'a> {
+        self.field(self.text.trim())
+    }
+
+    /// Parses the whole trimmed line.
+    pub fn parse<T: FromStr>(&self) -> Result<T, ParseError>
+    where
+        T::Err: fmt::Display,
+    {
+        self.trimmed().parse()
+    }
+
+    /// The whitespace-separated words of the line.
+    pub fn words(&self) -> impl Iterator<Item = Field<'a>> + '_ {
+        self.text.split_whitespace().map(|w| self.field(w))
+    }
+
+    /// Every run of digits in the line, parsed as an unsigned integer.
+    pub fn unsigned<T: FromStr>(&self) -> Result<Vec<T>, ParseError>
+    where
+        T::Err: fmt::Display,
+    {
+        self.integers(false)
+    }
+
+    /// Every run of digits in the line, with an optional leading `-`, parsed
+    /// as a signed integer.
+    pub fn signed<T: FromStr>(&self) -> Result<Vec<T>, ParseError>
+    where
+        T::Err: fmt::Display,
+    {
+        self.integers(true)
+    }
+
+    fn integers<T: FromStr>(&self, signed: bool) -> Result<Vec<T>, ParseError>
+    where
+        T::Err: fmt::Display,
+    {
+        let bytes = self.text.as_bytes();
+        let mut ret = vec![];
+        let mut i = 0;
+        while i < bytes.len() {
+            let starts_number = bytes[i].is_ascii_digit()
+                || (signed
+                    && bytes[i] == b'-'
+                    && bytes.get(i + 1).is_some_and(|b| b.is_ascii_digit()));
+            if !starts_number {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            ret.push(self.field(&self.text[start..i]).parse()?);
+        }
+        Ok(ret)
+    }
+
+    /// Splits the trimmed line around the first `separator`, as in
+    /// `key-value` or `A X`.
+    pub fn key_value(&self, separator: &str) -> Result<(Field<'a>, Field<'a>), ParseError> {
+        let trimmed = self.trimmed();
+        match trimmed.text.split_once(separator) {
+            Some((key, value)) if !key.is_empty() && !value.is_empty() => {
+                Ok((self.field(key), self.field(value)))
+            }
+            _ => Err(self.error(
+                trimmed.column,
+                format!("expected `key{}value`, found {:?}", separator, trimmed.text),
+            )),
+        }
+    }
+
+    /// Cuts the line into fixed-width columns of `width` bytes, the last one
+    /// possibly shorter.
+    pub fn columns(&self, width: usize) -> impl Iterator<Item = Field<'a>> + '_ {
+        assert!(width > 0, "column width must be positive");
+        (0..self.text.len())
+            .step_by(width)
+            .map(move |start| {
+                let end = (start + width).min(self.text.len());
+                self.field(&self.text[start..end])
+            })
+    }
+
+    /// Matches the trimmed line against `template`, where each `{}` captures
+    /// a non-empty field, and returns the captured fields in order.
+    ///
+    /// ```
+    /// # use advent_of_code_2022::aoc_common::Line;
+    /// let line = Line::new(1, "move 3 from 1 to 2");
+    /// let fields = line.template("move {} from {} to {}").unwrap();
+    /// assert_eq!(fields[0].parse::<usize>().unwrap(), 3);
+    /// ```
+    pub fn template(&self, template: &str) -> Result<Vec<Field<'a>>, ParseError> {
+        let trimmed = self.trimmed();
+        let literals = template.split("{}").collect::<Vec<_>>();
+        let mut rest = trimmed.text;
+        let mut ret = vec![];
+
+        let expect = |rest: &'a str, literal: &str| -> Result<&'a str, ParseError> {
+            rest.strip_prefix(literal).ok_or_else(|| {
+                self.error(
+                    self.field(rest).column,
+                    format!("expected {:?}, found {:?}", literal, rest),
+                )
+            })
+        };
+
+        rest = expect(rest, literals[0])?;
+        for (i, literal) in literals.iter().enumerate().skip(1) {
+            let end = if literal.is_empty() {
+                // Two captures in a row cannot be told apart
+                assert!(i == literals.len() - 1, "`{{}}{{}}` in template {:?}", template);
+                rest.len()
+            } else {
+                rest.find(literal).ok_or_else(|| {
+                    self.error(
+                        self.field(rest).column,
+                        format!("expected {:?} after {:?}", literal, rest),
+                    )
+                })?
+            };
+            if end == 0 {
+                return Err(self.error(
+                    self.field(rest).column,
+                    format!("expected a value before {:?}", literal),
+                ));
+            }
+            ret.push(self.field(&rest[..end]));
+            rest = expect(&rest[end..], literal)?;
+        }
+        if !rest.is_empty() {
+            return Err(self.error(
+                self.field(rest).column,
+                format!("unexpected trailing {:?}", rest),
+            ));
+        }
+        Ok(ret)
+    }
+}
+
+impl<'a> Field<'a> {
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            width: self.text.len().max(1),
+            message: message.into(),
+        }
+    }
+
+    pub fn parse<T: FromStr>(&self) -> Result<T, ParseError>
+    where
+        T::Err: fmt::Display,
+    {
+        self.text
+            .parse()
+            .map_err(|e| self.error(format!("cannot parse {:?}: {}", self.text, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_and_blocks() {
+        let input = "\r\n1\r\n2\r\n\r\n\r\n3\n\n";
+        let blocks = blocks(input);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0][0], Line { number: 2, text: "1" });
+        assert_eq!(blocks[1][0], Line { number: 6, text: "3" });
+        assert_eq!(Line::new(1, "abc\r\n").text, "abc");
+    }
+
+    #[test]
+    fn test_integers() {
+        let line = Line::new(3, "x=-12, y=7..-3");
+        assert_eq!(line.unsigned::<u32>().unwrap(), vec![12, 7, 3]);
+        assert_eq!(line.signed::<i32>().unwrap(), vec![-12, 7, -3]);
+        let err = Line::new(2, "a 300").unsigned::<u8>().unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+    }
+
+    #[test]
+    fn test_key_value() {
+        let line = Line::new(1, "  dir abc");
+        let (k, v) = line.key_value(" ").unwrap();
+        assert_eq!((k.text, k.column, v.text, v.column), ("dir", 3, "abc", 7));
+        assert!(Line::new(1, "dir").key_value(" ").is_err());
+    }
+
+    #[test]
+    fn test_columns() {
+        let line = Line::new(1, "[Z] [M] [P]");
+        let cells = line.columns(4).map(|f| (f.column, f.text)).collect::<Vec<_>>();
+        assert_eq!(cells, vec![(1, "[Z] "), (5, "[M] "), (9, "[P]")]);
+    }
+
+    #[test]
+    fn test_template() {
+        let line = Line::new(1, "2-4,6-8");
+        let fields = line.template("{}-{},{}-{}").unwrap();
+        assert_eq!(
+            fields.iter().map(|f| f.parse::<u32>().unwrap()).collect::<Vec<_>>(),
+            vec![2, 4, 6, 8]
+        );
+        let err = Line::new(5, "move 1 from 2 two 3")
+            .template("move {} from {} to {}")
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (5, 13));
+        let err = Line::new(5, "move x from 2 to 3")
+            .template("move {} from {} to {}")
+            .unwrap()[0]
+            .parse::<usize>()
+            .unwrap_err();
+        assert_eq!(err.column, 6);
+        assert!(Line::new(1, "move  from 2 to 3").template("move {} from {} to {}").is_err());
+        assert!(Line::new(1, "2-4,6-8,").template("{}-{},{}-{}").unwrap()[3]
+            .parse::<u32>()
+            .is_err());
+    }
+}
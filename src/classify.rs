@@ -0,0 +1,250 @@
+//! Guesses which day an input belongs to, for files that arrive without a
+//! usable name.
+//!
+//! Each day has a shape, scored from 0 to 1 by how many lines look like that
+//! day's lines. The day's parser then has the final word: an input it
+//! rejects scores 0 whatever its shape.
+
+use crate::{
+    aoc_common::{self, Line},
+    solution,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guess {
+    pub day: u8,
+    /// From 0 to 1.
+    pub confidence: f64,
+}
+
+type Shape = fn(&[Line]) -> f64;
+
+const SHAPES: &[(u8, Shape)] = &[
+    (1, calories),
+    (2, rounds),
+    (3, rucksacks),
+    (4, assignments),
+    (5, crates),
+    (6, signal),
+    (7, transcript),
+    (8, forest),
+];
+
+/// Every day the input could belong to, most likely first. Days whose
+/// parser rejects the input are left out.
+pub fn classify(input: &str) -> Vec<Guess> {
+    let lines = aoc_common::lines(input).collect::<Vec<_>>();
+    let mut ret = vec![];
+    for day in solution::registry() {
+        let Some((_, shape)) = SHAPES.iter().find(|(d, _)| *d == day.day()) else {
+            continue;
+        };
+        let confidence = shape(&lines);
+        if confidence > 0.0 && day.check(input).is_ok() {
+            ret.push(Guess {
+                day: day.day(),
+                confidence,
+            });
+        }
+    }
+    ret.sort_by(|a, b| b.confidence.total_cmp(&a.confidence).then(a.day.cmp(&b.day)));
+    ret
+}
+
+/// The most likely day, if any fits.
+pub fn best(input: &str) -> Option<Guess> {
+    classify(input).into_iter().next()
+}
+
+fn non_blank<'a>(lines: &'a [Line<'a>]) -> impl Iterator<Item = &'a str> {
+    lines.iter().filter(|l| !l.is_blank()).map(|l| l.text.trim())
+}
+
+/// The share of non-blank lines accepted by `f`, 0 without any.
+fn fraction(lines: &[Line], f: impl Fn(&str) -> bool) -> f64 {
+    let (mut matched, mut total) = (0, 0);
+    for text in non_blank(lines) {
+        total += 1;
+        if f(text) {
+            matched += 1;
+        }
+    }
+    match total {
+        0 => 0.0,
+        n => matched as f64 / n as f64,
+    }
+}
+
+/// Whether a blank line sits between two non-blank ones.
+fn has_separator(lines: &[Line]) -> bool {
+    let first = lines.iter().position(|l| !l.is_blank());
+    let last = lines.iter().rposition(|l| !l.is_blank());
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..last].iter().any(|l| l.is_blank()),
+        _ => false,
+    }
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Halves `score` when `condition` does not hold.
+fn unless(condition: bool, score: f64) -> f64 {
+    if condition {
+        score
+    } else {
+        score / 2.0
+    }
+}
+
+// 1000
+// 2000
+//
+// 4000
+fn calories(lines: &[Line]) -> f64 {
+    unless(has_separator(lines), fraction(lines, is_number))
+}
+
+// A Y
+fn rounds(lines: &[Line]) -> f64 {
+    fraction(lines, |t| matches!(t.as_bytes(), [b'A'..=b'C', b' ', b'X'..=b'Z']))
+}
+
+// vJrwpWtwJgWrhcsFMMfFFhFp
+fn rucksacks(lines: &[Line]) -> f64 {
+    let bag = |t: &str| t.len().is_multiple_of(2) && t.bytes().all(|b| b.is_ascii_alphabetic());
+    unless(non_blank(lines).nth(1).is_some(), fraction(lines, bag))
+}
+
+// 2-4,6-8
+fn assignments(lines: &[Line]) -> f64 {
+    let range = |r: &str| r.split_once('-').is_some_and(|(a, b)| is_number(a) && is_number(b));
+    fraction(lines, |t| t.split_once(',').is_some_and(|(a, b)| range(a) && range(b)))
+}
+
+//     [D]
+// [N] [C]
+//  1   2
+//
+// move 1 from 2 to 1
+fn crates(lines: &[Line]) -> f64 {
+    let Some(blank) = lines.iter().position(|l| l.is_blank()) else {
+        return 0.0;
+    };
+    let (drawing, commands) = lines.split_at(blank);
+    let pile = |t: &str| t.contains('[') || t.split_whitespace().all(is_number);
+    let command = |t: &str| {
+        matches!(
+            t.split_whitespace().collect::<Vec<_>>().as_slice(),
+            ["move", n, "from", a, "to", b] if [n, a, b].iter().all(|s| is_number(s))
+        )
+    };
+    let total = non_blank(lines).count() as f64;
+    let matched = non_blank(drawing).filter(|t| pile(t)).count() + non_blank(commands).filter(|t| command(t)).count();
+    unless(non_blank(commands).next().is_some(), matched as f64 / total)
+}
+
+// mjqjpqmgbljsphdztnvjfqwrcgsmlb
+fn signal(lines: &[Line]) -> f64 {
+    let mut texts = non_blank(lines);
+    match (texts.next(), texts.next()) {
+        // Too short to hold a start-of-message marker
+        (Some(t), None) if t.bytes().all(|b| b.is_ascii_lowercase()) => unless(t.len() >= 14, 1.0),
+        _ => 0.0,
+    }
+}
+
+// $ cd /
+// $ ls
+// dir a
+// 14848514 b.txt
+fn transcript(lines: &[Line]) -> f64 {
+    let entry = |t: &str| {
+        matches!(
+            t.split_whitespace().collect::<Vec<_>>().as_slice(),
+            ["$", "ls"] | ["$", "cd" | "mkdir" | "rm", _] | ["$", "mv", _, _] | ["dir", _]
+        ) || t.split_once(' ').is_some_and(|(size, _)| is_number(size))
+    };
+    let starts = non_blank(lines).next().is_some_and(|t| t.starts_with("$ "));
+    unless(starts, fraction(lines, entry))
+}
+
+// 30373
+// 25512
+fn forest(lines: &[Line]) -> f64 {
+    let width = non_blank(lines).next().map_or(0, str::len);
+    let score = fraction(lines, |t| is_number(t) && t.len() == width);
+    unless(width > 1 && non_blank(lines).nth(1).is_some() && !has_separator(lines), score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLES: &[(u8, &str)] = &[
+        (1, "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000"),
+        (2, "A Y\nB X\nC Z"),
+        (
+            3,
+            "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\n\
+             wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\nttgJtRGJQctTZtZT\nCrZsJsPPZsGzwwsLwLmpwMDw",
+        ),
+        (4, "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8"),
+        (
+            5,
+            "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+             move 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2",
+        ),
+        (6, "mjqjpqmgbljsphdztnvjfqwrcgsmlb"),
+        (
+            7,
+            "$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n$ cd a\n$ ls\ndir e\n29116 f\n\
+             2557 g\n62596 h.lst\n$ cd e\n$ ls\n584 i\n$ cd ..\n$ cd ..\n$ cd d\n$ ls\n4060174 j\n\
+             8033020 d.log\n5626152 d.ext\n7214296 k",
+        ),
+        (8, "30373\n25512\n65332\n33549\n35390"),
+    ];
+
+    #[test]
+    fn test_examples() {
+        for &(day, input) in EXAMPLES {
+            let guess = best(input).unwrap();
+            assert_eq!(guess, Guess { day, confidence: 1.0 }, "day {}", day);
+            let crlf = input.replace('\n', "\r\n");
+            assert_eq!(best(&crlf).unwrap().day, day, "day {} with CRLF", day);
+        }
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        // A digit grid also parses as one elf
+        let days = |input| classify(input).iter().map(|g| (g.day, g.confidence)).collect::<Vec<_>>();
+        assert_eq!(days("30373\n25512\n65332"), [(8, 1.0), (1, 0.5)]);
+        assert_eq!(days("aa\nbaab\ncaac"), [(3, 1.0)]);
+        assert_eq!(days("abcdef"), [(6, 0.5)]);
+        assert_eq!(days("1000\n2000\n\n4000"), [(1, 1.0), (8, 0.5)]);
+    }
+
+    #[test]
+    fn test_parser_has_the_last_word() {
+        // Shaped like day 4, but the second range does not fit in a u32
+        let input = "2-4,6-8\n2-3,4-99999999999";
+        assert_eq!(assignments(&aoc_common::lines(input).collect::<Vec<_>>()), 1.0);
+        assert!(classify(input).iter().all(|g| g.day != 4));
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert_eq!(classify(""), []);
+        assert_eq!(classify("hello, world!\n42 is the answer?"), []);
+    }
+
+    #[test]
+    fn test_partial_shape() {
+        // The day 5 parser skips what it cannot read in the drawing, but the
+        // stray title still lowers the confidence
+        let input = "Ship manifest\n[A]\n 1\n\nmove 1 from 1 to 1";
+        assert_eq!(best(input), Some(Guess { day: 5, confidence: 0.75 }));
+    }
+}
@@ -1,33 +1,189 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    io::{self, BufRead},
+    ops::Deref,
+};
+
 use aoc_runner_derive::{aoc, aoc_generator};
 
-#[aoc_generator(day1)]
-fn input_generator(input: &str) -> Vec<Vec<u32>> {
-    let mut ret = vec![vec![]];
-    for l in input.lines() {
-        let s = l.trim();
-        match s {
-            "" => ret.push(vec![]),
-            _ => ret.last_mut().unwrap().push(s.parse().unwrap()),
+use crate::aoc_common::{self, Line, ParseError};
+use crate::error::{self, PuzzleError};
+use crate::solution::Solution;
+use crate::trace_event;
+
+pub mod stats;
+
+/// Keeps the `k` largest elf totals seen so far in a min-heap, so memory
+/// stays bounded by `k` whatever the number of elves.
+struct TopK {
+    k: usize,
+    // Ties on the total are broken in favour of the earliest elf
+    heap: BinaryHeap<Reverse<(u64, Reverse<usize>)>>,
+}
+
+impl TopK {
+    fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    fn push(&mut self, elf: usize, total: u64) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse((total, Reverse(elf))));
+        } else if let Some(Reverse((min, _))) = self.heap.peek() {
+            if total > *min {
+                self.heap.pop();
+                self.heap.push(Reverse((total, Reverse(elf))));
+            }
+        }
+    }
+
+    /// Returns `(elf index, total)` pairs, largest total first.
+    fn into_sorted_vec(self) -> Vec<(usize, u64)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((total, Reverse(elf)))| (elf, total))
+            .collect()
+    }
+}
+
+/// Reads a calorie log from `reader` in a single pass and returns the `k`
+/// largest elf totals as `(elf index, total)` pairs, largest first.
+///
+/// Only one line and `k` totals are held in memory at any time, so this works
+/// on logs far larger than what `input_generator` can hold.
+pub fn top_k_totals<R: BufRead>(mut reader: R, k: usize) -> io::Result<Vec<(usize, u64)>> {
+    let mut top = TopK::new(k);
+    let mut line = String::new();
+    let mut elf = 0;
+    let mut total: Option<u64> = None;
+    let mut line_number = 0;
+    while reader.read_line(&mut line)? > 0 {
+        line_number += 1;
+        let l = Line::new(line_number, &line);
+        if l.is_blank() {
+            // Runs of blank lines only close the current elf once
+            if let Some(t) = total.take() {
+                top.push(elf, t);
+                elf += 1;
+            }
+        } else {
+            let calories = l
+                .parse::<u64>()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let t = total.unwrap_or(0).checked_add(calories).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    l.error(1, format!("total of elf {} overflows", elf)),
+                )
+            })?;
+            total = Some(t);
+        }
+        line.clear();
+    }
+    if let Some(t) = total {
+        top.push(elf, t);
+    }
+    Ok(top.into_sorted_vec())
+}
+
+/// Every elf's snacks, in log order. The grand total fits in a `u64`, so no
+/// sum the parts make can overflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "Vec<Vec<u64>>", try_from = "Vec<Vec<u64>>"))]
+pub struct Inventories(Vec<Vec<u64>>);
+
+impl Deref for Inventories {
+    type Target = [Vec<u64>];
+
+    fn deref(&self) -> &[Vec<u64>] {
+        &self.0
+    }
+}
+
+impl From<Inventories> for Vec<Vec<u64>> {
+    fn from(inventories: Inventories) -> Self {
+        inventories.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Vec<Vec<u64>>> for Inventories {
+    type Error = String;
+
+    fn try_from(elves: Vec<Vec<u64>>) -> Result<Self, String> {
+        match elves.iter().flatten().try_fold(0u64, |total, &c| total.checked_add(c)) {
+            Some(_) => Ok(Self(elves)),
+            None => Err(String::from("total calories overflow")),
+        }
+    }
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+    type Input = Inventories;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        // Bounding the grand total keeps every sum the parts make in range
+        let mut total = 0u64;
+        let mut ret = vec![];
+        for elf in aoc_common::blocks(input) {
+            let mut snacks = vec![];
+            for l in elf.iter() {
+                let calories = l.parse()?;
+                total = total
+                    .checked_add(calories)
+                    .ok_or_else(|| l.error(1, "total calories overflow"))?;
+                snacks.push(calories);
+            }
+            ret.push(snacks);
         }
+        Ok(Inventories(ret))
+    }
+
+    fn part1(input: &Self::Input) -> u64 {
+        input.iter().map(|e| e.iter().sum()).max().unwrap_or(0)
+    }
+
+    fn part2(input: &Self::Input) -> u64 {
+        let mut top = TopK::new(3);
+        for (elf, snacks) in input.iter().enumerate() {
+            top.push(elf, snacks.iter().sum());
+        }
+        let top = top.into_sorted_vec();
+        trace_event!(Day1::DAY, Info, "top_elves", elves = top);
+        top.iter().map(|(_, total)| total).sum()
     }
-    return ret;
+}
+
+#[aoc_generator(day1)]
+pub fn input_generator(input: &str) -> Result<Inventories, PuzzleError> {
+    error::parse::<Day1>(input)
 }
 
 #[aoc(day1, part1)]
-fn solve_part1(input: &[Vec<u32>]) -> u32 {
-    input.iter().map(|e| e.iter().sum()).max().unwrap()
+pub fn solve_part1(input: &Inventories) -> u64 {
+    Day1::part1(input)
 }
 
 #[aoc(day1, part2)]
-fn solve_part2(input: &[Vec<u32>]) -> u32 {
-    let mut elves = input.iter().map(|e| e.iter().sum()).collect::<Vec<u32>>();
-    elves.sort();
-    elves[elves.len() - 3..].iter().sum()
+pub fn solve_part2(input: &Inventories) -> u64 {
+    Day1::part2(input)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_part1() {
@@ -45,7 +201,7 @@ mod tests {
             9000
             
             10000";
-        assert_eq!(solve_part1(&input_generator(input)), 24000);
+        assert_eq!(solve_part1(&input_generator(input).unwrap()), 24000);
     }
 
     #[test]
@@ -64,6 +220,73 @@ mod tests {
             9000
             
             10000";
-        assert_eq!(solve_part2(&input_generator(input)), 45000);
+        assert_eq!(solve_part2(&input_generator(input).unwrap()), 45000);
+    }
+
+    #[test]
+    fn test_top_k_totals() {
+        let input = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+        assert_eq!(
+            top_k_totals(input.as_bytes(), 3).unwrap(),
+            vec![(3, 24000), (2, 11000), (4, 10000)]
+        );
+        assert_eq!(top_k_totals(input.as_bytes(), 0).unwrap(), vec![]);
+        assert!(top_k_totals("1000\nabc\n".as_bytes(), 3).is_err());
+    }
+
+    #[test]
+    fn test_input_generator_blank_lines() {
+        let input = "\n1000\r\n2000\r\n\r\n\r\n\r\n3000\r\n\r\n\r\n";
+        assert_eq!(*input_generator(input).unwrap(), [vec![1000, 2000], vec![3000]]);
+        assert_eq!(
+            top_k_totals(input.as_bytes(), 3).unwrap(),
+            vec![(0, 3000), (1, 3000)]
+        );
+    }
+
+    #[test]
+    fn test_input_generator_errors() {
+        let err = input_generator("1000\n\n2000\n2x00").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.width, err.message.as_str()),
+            (4, 1, 4, "cannot parse \"2x00\": invalid digit found in string")
+        );
+        // Totals beyond u32::MAX are fine
+        let input = "4000000000\n4000000000";
+        assert_eq!(solve_part1(&input_generator(input).unwrap()), 8_000_000_000);
+        let input = "18446744073709551615\n1";
+        assert!(top_k_totals(input.as_bytes(), 1).is_err());
+        let err = input_generator("18446744073709551615\n\n1").unwrap_err();
+        assert_eq!((err.line, err.message.as_str()), (3, "total calories overflow"));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        // Found by fuzzing, part 1 used to unwrap the largest of no totals
+        assert_eq!(solve_part1(&input_generator("").unwrap()), 0);
+        assert_eq!(solve_part2(&input_generator("\n\n").unwrap()), 0);
+    }
+
+    /// Sorts every total, as `solve_part2` used to.
+    fn top_k_naive(totals: &[u64], k: usize) -> Vec<u64> {
+        let mut totals = totals.to_vec();
+        totals.sort_unstable_by(|a, b| b.cmp(a));
+        totals.truncate(k);
+        totals
+    }
+
+    proptest! {
+        #[test]
+        fn prop_top_k_matches_sort(totals in prop::collection::vec(0..50u64, 0..30), k in 0..5usize) {
+            let mut top = TopK::new(k);
+            for (elf, &total) in totals.iter().enumerate() {
+                top.push(elf, total);
+            }
+            let top = top.into_sorted_vec();
+            prop_assert_eq!(top.iter().map(|&(_, t)| t).collect::<Vec<_>>(), top_k_naive(&totals, k));
+            for (elf, total) in top {
+                prop_assert_eq!(totals[elf], total);
+            }
+        }
     }
 }
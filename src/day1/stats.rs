@@ -0,0 +1,213 @@
+use std::fmt;
+
+/// Number of elves whose total falls in `[start, end)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bucket {
+    pub start: u64,
+    pub end: u64,
+    pub count: usize,
+}
+
+/// The single largest snack and the elf carrying it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargestSnack {
+    pub elf: usize,
+    pub calories: u64,
+}
+
+/// Summary of the per-elf calorie totals of a day 1 input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    pub elves: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    pub histogram: Vec<Bucket>,
+    pub empty_elves: Vec<usize>,
+    pub largest_snack: Option<LargestSnack>,
+}
+
+impl Statistics {
+    /// Computes the statistics of `inventories`, spreading the totals over
+    /// `buckets` equal-width histogram buckets. Totals saturate at
+    /// `u64::MAX`.
+    pub fn new(inventories: &[Vec<u64>], buckets: usize) -> Self {
+        let totals = inventories
+            .iter()
+            .map(|e| e.iter().fold(0u64, |a, &b| a.saturating_add(b)))
+            .collect::<Vec<u64>>();
+        let elves = totals.len();
+
+        let mean = if elves == 0 {
+            0.0
+        } else {
+            totals.iter().map(|&t| t as u128).sum::<u128>() as f64 / elves as f64
+        };
+        let std_dev = if elves == 0 {
+            0.0
+        } else {
+            let variance = totals
+                .iter()
+                .map(|&t| (t as f64 - mean).powi(2))
+                .sum::<f64>()
+                / elves as f64;
+            variance.sqrt()
+        };
+
+        let mut sorted = totals.clone();
+        sorted.sort_unstable();
+        let median = match elves {
+            0 => 0.0,
+            n if n % 2 == 1 => sorted[n / 2] as f64,
+            // Halving first so that two large totals cannot overflow
+            n => {
+                let (a, b) = (sorted[n / 2 - 1], sorted[n / 2]);
+                (a / 2 + b / 2) as f64 + ((a % 2 + b % 2) as f64 / 2.0)
+            }
+        };
+
+        let empty_elves = inventories
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.is_empty())
+            .map(|(i, _)| i)
+            .collect();
+
+        let mut largest_snack: Option<LargestSnack> = None;
+        for (elf, snacks) in inventories.iter().enumerate() {
+            for &calories in snacks {
+                if largest_snack.as_ref().is_none_or(|l| calories > l.calories) {
+                    largest_snack = Some(LargestSnack { elf, calories });
+                }
+            }
+        }
+
+        Self {
+            elves,
+            mean,
+            median,
+            std_dev,
+            histogram: histogram(&sorted, buckets),
+            empty_elves,
+            largest_snack,
+        }
+    }
+
+    /// Renders the statistics as a single JSON object.
+    pub fn to_json(&self) -> String {
+        let histogram = self
+            .histogram
+            .iter()
+            .map(|b| format!(r#"{{"start":{},"end":{},"count":{}}}"#, b.start, b.end, b.count))
+            .collect::<Vec<_>>()
+            .join(",");
+        let empty_elves = self
+            .empty_elves
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let largest_snack = match &self.largest_snack {
+            Some(l) => format!(r#"{{"elf":{},"calories":{}}}"#, l.elf, l.calories),
+            None => String::from("null"),
+        };
+        format!(
+            r#"{{"elves":{},"mean":{},"median":{},"std_dev":{},"histogram":[{}],"empty_elves":[{}],"largest_snack":{}}}"#,
+            self.elves, self.mean, self.median, self.std_dev, histogram, empty_elves, largest_snack
+        )
+    }
+}
+
+/// Splits the sorted totals into `buckets` equal-width buckets covering
+/// `[min, max]`, the last bucket being widened to include `max`. Bounds
+/// saturate at `u64::MAX`.
+fn histogram(sorted: &[u64], buckets: usize) -> Vec<Bucket> {
+    let (min, max) = match (sorted.first(), sorted.last()) {
+        (Some(&min), Some(&max)) if buckets > 0 => (min, max),
+        _ => return vec![],
+    };
+    let width = ((max - min) / buckets as u64).saturating_add(1);
+    let mut ret = (0..buckets as u64)
+        .map(|i| Bucket {
+            start: min.saturating_add(i.saturating_mul(width)),
+            end: min.saturating_add((i + 1).saturating_mul(width)),
+            count: 0,
+        })
+        .collect::<Vec<_>>();
+    for &total in sorted {
+        let i = (((total - min) / width) as usize).min(buckets - 1);
+        ret[i].count += 1;
+    }
+    ret
+}
+
+impl fmt::Display for Statistics {
+    /// Renders the statistics as a plain text table.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<15} {:>12}", "elves", self.elves)?;
+        writeln!(f, "{:<15} {:>12.2}", "mean", self.mean)?;
+        writeln!(f, "{:<15} {:>12.2}", "median", self.median)?;
+        writeln!(f, "{:<15} {:>12.2}", "std dev", self.std_dev)?;
+        writeln!(f, "{:<15} {:>12}", "empty elves", self.empty_elves.len())?;
+        match &self.largest_snack {
+            Some(l) => writeln!(f, "{:<15} {:>12} (elf {})", "largest snack", l.calories, l.elf)?,
+            None => writeln!(f, "{:<15} {:>12}", "largest snack", "-")?,
+        }
+        writeln!(f, "histogram")?;
+        for b in self.histogram.iter() {
+            writeln!(f, "  {:>8} - {:<8} {:>6}", b.start, b.end, b.count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statistics() {
+        let inventories = vec![
+            vec![1000, 2000, 3000],
+            vec![4000],
+            vec![5000, 6000],
+            vec![],
+            vec![7000, 8000, 9000],
+            vec![10000],
+        ];
+        let stats = Statistics::new(&inventories, 3);
+        assert_eq!(stats.elves, 6);
+        assert_eq!(stats.mean, 55000.0 / 6.0);
+        assert_eq!(stats.median, 8000.0);
+        assert!((stats.std_dev - 7581.04).abs() < 0.1);
+        assert_eq!(stats.empty_elves, vec![3]);
+        assert_eq!(stats.largest_snack, Some(LargestSnack { elf: 5, calories: 10000 }));
+        assert_eq!(
+            stats.histogram.iter().map(|b| b.count).collect::<Vec<_>>(),
+            vec![3, 2, 1]
+        );
+        assert!(stats.to_json().starts_with(r#"{"elves":6,"#));
+        assert!(stats.to_json().contains(r#""empty_elves":[3]"#));
+        assert!(stats.to_string().contains("empty elves"));
+    }
+
+    #[test]
+    fn test_statistics_empty() {
+        let stats = Statistics::new(&[], 4);
+        assert_eq!(stats.elves, 0);
+        assert!(stats.histogram.is_empty());
+        assert_eq!(stats.largest_snack, None);
+        assert!(stats.to_json().ends_with(r#""largest_snack":null}"#));
+    }
+
+    #[test]
+    fn test_no_overflow() {
+        let stats = Statistics::new(&[vec![u64::MAX], vec![u64::MAX - 2]], 1);
+        assert_eq!(stats.median, (u64::MAX - 1) as f64);
+        let stats = Statistics::new(&[vec![3], vec![4]], 1);
+        assert_eq!(stats.median, 3.5);
+        let stats = Statistics::new(&[vec![u64::MAX, 1], vec![0]], 1);
+        assert_eq!(stats.largest_snack, Some(LargestSnack { elf: 0, calories: u64::MAX }));
+        assert_eq!(stats.histogram, [Bucket { start: 0, end: u64::MAX, count: 2 }]);
+    }
+}
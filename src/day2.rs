@@ -1,5 +1,12 @@
+use std::ops::Deref;
+
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::aoc_common::{self, ParseError};
+use crate::error::{self, PuzzleError};
+use crate::solution::Solution;
+use crate::trace_event;
+
 enum Choices {
     Rock     = 1,
     Paper    = 2,
@@ -9,28 +16,6 @@ enum Choices {
     Z        = 6,
 }
 
-#[aoc_generator(day2)]
-fn parse_input(input: &str) -> Vec<Vec<u32>> {
-    let mut ret = Vec::new();
-    for l in input.lines() {
-        let s = l.trim().split(" ");
-        let mut round = Vec::<u32>::new();
-        for c in s {
-            match c {
-                "A" => round.push(Choices::Rock as u32),
-                "B" => round.push(Choices::Paper as u32),
-                "C" => round.push(Choices::Scissors as u32),
-                "X" => round.push(Choices::X as u32),
-                "Y" => round.push(Choices::Y as u32),
-                "Z" => round.push(Choices::Z as u32),
-                _ => panic!("Invalid input"),
-            }
-        }
-        ret.push(round);
-    }
-    return ret;
-}
-
 enum WinState {
     Win = 6,
     Lose = 0,
@@ -67,14 +52,93 @@ fn shifumi_2(a: u32, b: u32) -> u32 {
     }
 }
 
+/// Rounds as `[opponent, response]` pairs, the opponent's shape from 1 to 3
+/// and the response from 4 to 6.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "Vec<Vec<u32>>", try_from = "Vec<Vec<u32>>"))]
+pub struct Rounds(Vec<Vec<u32>>);
+
+impl Deref for Rounds {
+    type Target = [Vec<u32>];
+
+    fn deref(&self) -> &[Vec<u32>] {
+        &self.0
+    }
+}
+
+impl From<Rounds> for Vec<Vec<u32>> {
+    fn from(rounds: Rounds) -> Self {
+        rounds.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Vec<Vec<u32>>> for Rounds {
+    type Error = String;
+
+    fn try_from(rounds: Vec<Vec<u32>>) -> Result<Self, String> {
+        for (i, round) in rounds.iter().enumerate() {
+            if !matches!(round.as_slice(), [1..=3, 4..=6]) {
+                return Err(format!("round {}: expected [1-3, 4-6], found {:?}", i, round));
+            }
+        }
+        Ok(Self(rounds))
+    }
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    type Input = Rounds;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let mut ret = Vec::new();
+        for l in aoc_common::lines(input) {
+            let (a, b) = l.key_value(" ")?;
+            let opponent = match a.text {
+                "A" => Choices::Rock,
+                "B" => Choices::Paper,
+                "C" => Choices::Scissors,
+                _ => return Err(a.error(format!("expected A, B or C, found {:?}", a.text))),
+            };
+            let response = match b.text {
+                "X" => Choices::X,
+                "Y" => Choices::Y,
+                "Z" => Choices::Z,
+                _ => return Err(b.error(format!("expected X, Y or Z, found {:?}", b.text))),
+            };
+            ret.push(vec![opponent as u32, response as u32]);
+        }
+        Ok(Rounds(ret))
+    }
+
+    fn part1(input: &Self::Input) -> u32 {
+        trace_event!(Day2::DAY, Info, "rounds", count = input.len());
+        input.iter().map(|e| shifumi(e[0], e[1])).sum()
+    }
+
+    fn part2(input: &Self::Input) -> u32 {
+        input.iter().map(|e| shifumi_2(e[0], e[1])).sum()
+    }
+}
+
+#[aoc_generator(day2)]
+pub fn parse_input(input: &str) -> Result<Rounds, PuzzleError> {
+    error::parse::<Day2>(input)
+}
+
 #[aoc(day2, part1)]
-fn solve_part1(input: &[Vec<u32>]) -> u32 {
-    input.iter().map(|e| shifumi(e[0], e[1])).sum()
+pub fn solve_part1(input: &Rounds) -> u32 {
+    Day2::part1(input)
 }
 
 #[aoc(day2, part2)]
-fn solve_part2(input: &[Vec<u32>]) -> u32 {
-    input.iter().map(|e| shifumi_2(e[0], e[1])).sum()
+pub fn solve_part2(input: &Rounds) -> u32 {
+    Day2::part2(input)
 }
 
 #[cfg(test)]
@@ -86,7 +150,7 @@ mod tests {
         let input = "A Y
         B X
         C Z";
-        assert_eq!(solve_part1(&parse_input(input)), 15);
+        assert_eq!(solve_part1(&parse_input(input).unwrap()), 15);
     }
 
     #[test]
@@ -94,6 +158,13 @@ mod tests {
         let input = "A Y
         B X
         C Z";
-        assert_eq!(solve_part2(&parse_input(input)), 12);
+        assert_eq!(solve_part2(&parse_input(input).unwrap()), 12);
+    }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input("A Y\nB W").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert!(parse_input("A Y\nBX").is_err());
     }
 }
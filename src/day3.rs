@@ -1,4 +1,11 @@
-use aoc_runner_derive::{aoc};
+use std::ops::Deref;
+
+use aoc_runner_derive::{aoc, aoc_generator};
+
+use crate::aoc_common::{self, ParseError};
+use crate::error::{self, PuzzleError};
+use crate::solution::Solution;
+use crate::trace_event;
 
 fn unique_items(s: &str) -> u64 {
     s.bytes()
@@ -10,22 +17,128 @@ fn unique_items(s: &str) -> u64 {
     .fold(0, |acc, b| acc | (1u64 << b))
 }
 
+/// Checks what both parts rely on: bags of an even number of item letters
+/// whose halves share an item, in groups of three sharing a badge. Errors
+/// come with the index of the offending bag, the first of its group for
+/// group errors.
+fn check_bags(bags: &[String]) -> Result<(), (usize, String)> {
+    for (i, bag) in bags.iter().enumerate() {
+        let error = |message: String| Err((i, message));
+        if let Some(c) = bag.chars().find(|c| !c.is_ascii_alphabetic()) {
+            return error(format!("expected an item letter, found {:?}", c));
+        }
+        if bag.is_empty() {
+            return error(String::from("the bag is empty"));
+        }
+        if bag.len() % 2 == 1 {
+            return error(format!("expected an even number of items, found {}", bag.len()));
+        }
+        let (l, r) = bag.split_at(bag.len() / 2);
+        if unique_items(l) & unique_items(r) == 0 {
+            return error(String::from("the compartments share no item"));
+        }
+    }
+    for (i, group) in bags.chunks(3).enumerate() {
+        let error = |message: String| Err((3 * i, message));
+        if group.len() < 3 {
+            return error(format!("expected a group of 3 bags, found {}", group.len()));
+        }
+        if group.iter().map(|b| unique_items(b)).fold(u64::MAX, |a, b| a & b) == 0 {
+            return error(String::from("the group shares no badge"));
+        }
+    }
+    Ok(())
+}
+
+/// Rucksacks as checked by [`check_bags`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "Vec<String>", try_from = "Vec<String>"))]
+pub struct Rucksacks(Vec<String>);
+
+impl Deref for Rucksacks {
+    type Target = [String];
+
+    fn deref(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl From<Rucksacks> for Vec<String> {
+    fn from(bags: Rucksacks) -> Self {
+        bags.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Vec<String>> for Rucksacks {
+    type Error = String;
+
+    fn try_from(bags: Vec<String>) -> Result<Self, String> {
+        match check_bags(&bags) {
+            Ok(()) => Ok(Self(bags)),
+            Err((i, message)) => Err(format!("bag {}: {}", i, message)),
+        }
+    }
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+    type Input = Rucksacks;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let lines = aoc_common::lines(input).collect::<Vec<_>>();
+        let mut bags = vec![];
+        for l in lines.iter() {
+            let bag = l.trimmed();
+            if let Some(i) = bag.text.find(|c: char| !c.is_ascii_alphabetic()) {
+                return Err(l.error(bag.column + i, "expected an item letter"));
+            }
+            bags.push(bag.text.to_string());
+        }
+        check_bags(&bags).map_err(|(i, message)| match lines[i].trimmed() {
+            bag if bag.text.is_empty() => lines[i].error(1, message),
+            bag => bag.error(message),
+        })?;
+        Ok(Rucksacks(bags))
+    }
+
+    fn part1(input: &Self::Input) -> u32 {
+        input.iter()
+        .map(|bag| bag.split_at(bag.len() / 2))
+        .map(|(l, r)| [l, r].map(unique_items))
+        .map(|[l, r]| u64::trailing_zeros(l & r))
+        .inspect(|priority| trace_event!(Day3::DAY, Trace, "shared_item", priority = priority))
+        .sum()
+    }
+
+    fn part2(input: &Self::Input) -> u32 {
+        input.chunks_exact(3)
+        .map(|bags| [&bags[0], &bags[1], &bags[2]].map(|b| unique_items(b)))
+        .map(|[a, b, c]| a & b & c)
+        .map(u64::trailing_zeros)
+        .inspect(|priority| trace_event!(Day3::DAY, Trace, "badge", priority = priority))
+        .sum()
+    }
+}
+
+#[aoc_generator(day3)]
+pub fn input_generator(input: &str) -> Result<Rucksacks, PuzzleError> {
+    error::parse::<Day3>(input)
+}
+
 #[aoc(day3, part1)]
-fn solve_part1(input: &str) -> u32 {
-    input.lines()
-    .map(|bag| bag.split_at(bag.len() / 2))
-    .map(|(l, r)| [l, r].map(unique_items))
-    .map(|[l, r]| u64::trailing_zeros(l & r))
-    .sum()
+pub fn solve_part1(input: &Rucksacks) -> u32 {
+    Day3::part1(input)
 }
 
 #[aoc(day3, part2)]
-fn solve_part2(input: &str) -> u32 {
-    input.lines().array_chunks::<3>()
-    .map(|bags| bags.map(unique_items))
-    .map(|[a, b, c]| a & b & c)
-    .map(u64::trailing_zeros)
-    .sum()
+pub fn solve_part2(input: &Rucksacks) -> u32 {
+    Day3::part2(input)
 }
 
 #[cfg(test)]
@@ -35,12 +148,28 @@ mod tests {
     #[test]
     fn test_part1() {
         let input = "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\nwMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\nttgJtRGJQctTZtZT\nCrZsJsPPZsGzwwsLwLmpwMDw";
-        assert_eq!(solve_part1(input), 157);
+        assert_eq!(solve_part1(&input_generator(input).unwrap()), 157);
     }
 
     #[test]
     fn test_part2() {
         let input = "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\nwMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\nttgJtRGJQctTZtZT\nCrZsJsPPZsGzwwsLwLmpwMDw";
-        assert_eq!(solve_part2(input), 70);
+        assert_eq!(solve_part2(&input_generator(input).unwrap()), 70);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_input_generator_errors() {
+        let err = input_generator("vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRj1zjGDLGL").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 9));
+        let err = |input: &str| {
+            let err = input_generator(input).unwrap_err();
+            (err.line, err.column, err.width, err.message)
+        };
+        // Each of these used to count a priority of 64, or drop bags
+        assert_eq!(err("aa\nabc\naa"), (2, 1, 3, String::from("expected an even number of items, found 3")));
+        assert_eq!(err("aa\nab\naa"), (2, 1, 2, String::from("the compartments share no item")));
+        assert_eq!(err("aa\n\naa"), (2, 1, 1, String::from("the bag is empty")));
+        assert_eq!(err("aa\naa\naa\nbb"), (4, 1, 2, String::from("expected a group of 3 bags, found 1")));
+        assert_eq!(err("aa\naa\nbb\naa"), (1, 1, 2, String::from("the group shares no badge")));
+    }
+}
@@ -1,6 +1,13 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 
-struct Assignment {
+use crate::aoc_common::{self, ParseError};
+use crate::error::{self, PuzzleError};
+use crate::solution::Solution;
+use crate::trace_event;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Assignment {
     first_team: [u32; 2],
     second_team: [u32; 2],
 }
@@ -12,7 +19,19 @@ impl Assignment {
             second_team,
         }
     }
-    
+
+    /// The first and last sections of both teams.
+    pub fn sections(&self) -> ([u32; 2], [u32; 2]) {
+        (self.first_team, self.second_team)
+    }
+
+    /// Whether the two ranges share at least one section.
+    fn overlaps(&self) -> bool {
+        self.first_team[0] <= self.second_team[1] && self.second_team[0] <= self.first_team[1]
+    }
+
+    // Enumerates every section, only used to check `overlaps` against
+    #[cfg(test)]
     fn create_ranges(&self) -> [Vec<u32>; 2]{
         let mut first_team_range = Vec::new();
         let mut second_team_range = Vec::new();
@@ -29,79 +48,104 @@ impl Assignment {
     }
 }
 
-#[aoc_generator(day4)]
-fn input_generator(input: &str) -> Vec<Assignment> {
-    // One line example: 2-4,6-8
-    // Parse each line into an assignment
-    // Return a vector of assignments
-    let mut assignments = Vec::new();
-    for line in input.lines() {
-        let data = line.split(',');
-        let mut first_team = [0; 2];
-        let mut second_team = [0; 2];
-        for (i, d) in data.enumerate() {
-            let mut team = [0; 2];
-            for (j, n) in d.split('-').enumerate() {
-                team[j] = n.parse().unwrap();
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+    type Input = Vec<Assignment>;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        // One line example: 2-4,6-8
+        // Parse each line into an assignment
+        // Return a vector of assignments
+        let mut assignments = Vec::new();
+        for line in aoc_common::lines(input) {
+            let fields = line.template("{}-{},{}-{}")?;
+            let mut bounds = [0; 4];
+            for (bound, field) in bounds.iter_mut().zip(fields.iter()) {
+                *bound = field.parse()?;
             }
-            if i == 0 {
-                first_team = team;
-            } else {
-                second_team = team;
+            assignments.push(Assignment::new([bounds[0], bounds[1]], [bounds[2], bounds[3]]));
+        }
+        Ok(assignments)
+    }
+
+    fn part1(input: &Self::Input) -> u32 {
+        // In how many assignment pairs does one range fully contain the other?
+        let mut count = 0;
+        for assignment in input {
+            if (assignment.first_team[0] >= assignment.second_team[0] && assignment.first_team[1] <= assignment.second_team[1])
+                || (assignment.second_team[0] >= assignment.first_team[0] && assignment.second_team[1] <= assignment.first_team[1])
+            {
+                trace_event!(Day4::DAY, Trace, "contained", assignment = assignment);
+                count += 1;
             }
         }
-        assignments.push(Assignment::new(first_team, second_team));
+        count
+    }
+
+    fn part2(input: &Self::Input) -> u32 {
+        // In how many assignment pairs do the ranges overlap?
+        input.iter().filter(|a| a.overlaps()).count() as u32
     }
-    assignments
+}
+
+#[aoc_generator(day4)]
+pub fn input_generator(input: &str) -> Result<Vec<Assignment>, PuzzleError> {
+    error::parse::<Day4>(input)
 }
 
 #[aoc(day4, part1)]
-fn solve_part1(input: &Vec<Assignment>) -> u32 {
-    // In how many assignment pairs does one range fully contain the other?
-    let mut count = 0;
-    for assignment in input {
-        if assignment.first_team[0] >= assignment.second_team[0] && assignment.first_team[1] <= assignment.second_team[1] {
-            count += 1;
-        } else if assignment.second_team[0] >= assignment.first_team[0] && assignment.second_team[1] <= assignment.first_team[1] {
-            count += 1;
-        }
-    }
-    count
+pub fn solve_part1(input: &Vec<Assignment>) -> u32 {
+    Day4::part1(input)
 }
 
 #[aoc(day4, part2)]
-fn solve_part2(input: &[Assignment]) -> u32 {
-    // In how many assignment pairs do the ranges overlap?
-    let mut count = 0;
-    for assignment in input {
-        let ranges = assignment.create_ranges();
-        let mut overlap = false;
-        for i in ranges[0].iter() {
-            if ranges[1].contains(i) {
-                overlap = true;
-                break;
-            }
-        }
-        if overlap {
-            count += 1;
-        }
-    }
-    count
+pub fn solve_part2(input: &Vec<Assignment>) -> u32 {
+    Day4::part2(input)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_part1() {
         let input = "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8";
-        assert_eq!(solve_part1(&input_generator(input)), 2);
+        assert_eq!(solve_part1(&input_generator(input).unwrap()), 2);
     }
 
     #[test]
     fn test_part2() {
         let input = "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8";
-        assert_eq!(solve_part2(&input_generator(input)), 4);
+        assert_eq!(solve_part2(&input_generator(input).unwrap()), 4);
+    }
+
+    #[test]
+    fn test_input_generator_errors() {
+        let err = input_generator("2-4,6-8\n2-3;4-5").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        let err = input_generator("2-4,6-x").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 7));
     }
-}
\ No newline at end of file
+
+    fn overlaps_naive(assignment: &Assignment) -> bool {
+        let ranges = assignment.create_ranges();
+        ranges[0].iter().any(|i| ranges[1].contains(i))
+    }
+
+    fn assignment() -> impl Strategy<Value = Assignment> {
+        (0..100u32, 0..20u32, 0..100u32, 0..20u32)
+            .prop_map(|(a, la, b, lb)| Assignment::new([a, a + la], [b, b + lb]))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_overlaps_matches_ranges(assignment in assignment()) {
+            prop_assert_eq!(assignment.overlaps(), overlaps_naive(&assignment));
+        }
+    }
+}
@@ -2,6 +2,13 @@ use std::{collections::HashMap, vec};
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::aoc_common::{self, Line, ParseError};
+use crate::error::{self, PuzzleError};
+use crate::solution::Solution;
+use crate::trace_event;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct ShipCommand {
     movement: (usize, usize),
     n_crates: usize,
@@ -23,10 +30,44 @@ impl ShipCommand {
         }
     }
 }
-struct Ship {
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ShipData"))]
+pub struct Ship {
+    piles: HashMap<usize, Vec<char>>,
+    commands: Vec<ShipCommand>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ShipData {
     piles: HashMap<usize, Vec<char>>,
     commands: Vec<ShipCommand>,
-    cursor: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ShipData> for Ship {
+    type Error = String;
+
+    fn try_from(data: ShipData) -> Result<Self, String> {
+        // The same checks as the parser, see `modelize_command`
+        let mut heights = data.piles.iter().map(|(&i, pile)| (i, pile.len())).collect::<HashMap<_, _>>();
+        for (i, command) in data.commands.iter().enumerate() {
+            let (from, to, n) = command.get_command();
+            if let Some(pile) = [from, to].into_iter().find(|p| !heights.contains_key(p)) {
+                return Err(format!("command {}: there is no pile {}", i, pile));
+            }
+            if n > heights[&from] {
+                return Err(format!("command {}: pile {} only has {} crates", i, from, heights[&from]));
+            }
+            *heights.get_mut(&from).unwrap() -= n;
+            *heights.get_mut(&to).unwrap() += n;
+        }
+        Ok(Self {
+            piles: data.piles,
+            commands: data.commands,
+        })
+    }
 }
 
 impl Ship {
@@ -34,56 +75,66 @@ impl Ship {
         Ship {
             piles: HashMap::new(),
             commands: vec![],
-            cursor: 1,
         }
     }
 
-    fn print_piles(&self, msg: &str) {
-        println!("{}", msg);
-        // Print piles in order
-        // Reverse them first
-        let piles = self.piles.clone();
-        let mut keys = piles.keys().collect::<Vec<&usize>>();
-        // Sort keys in reverse order
-        keys.sort_by(|a, b| b.cmp(a));
-        keys.reverse();
-        for key in keys {
-            let pile = piles.get(key).unwrap();
-            // Print optional message
-            println!("Pile {}: {:?}", key, pile);
+    fn modelize_drawing(&mut self, drawing: &[Line]) -> Result<(), ParseError> {
+        // The last line numbers the piles: ` 1   2   3 `
+        let Some((labels, crates)) = drawing.split_last() else {
+            return Ok(());
+        };
+        for word in labels.words() {
+            match word.parse()? {
+                0 => return Err(word.error("piles are numbered from 1")),
+                n => {
+                    if self.piles.insert(n, vec![]).is_some() {
+                        return Err(word.error(format!("pile {} is numbered twice", n)));
+                    }
+                }
+            }
+        }
+        for line in crates {
+            self.modelize_line(line)?;
         }
+        Ok(())
     }
 
-    fn modelize_line(&mut self, line: &str) {
-        for (i, c) in line.chars().enumerate() {
-            if i == 0 {
-                self.cursor = 0
-            }
-            if i % 4 == 1 {
-                self.cursor += 1
-            }
-            match c {
-                'A'..='Z' => {
-                    // Make a crate
-                    self.piles.entry(self.cursor).or_insert(vec![]).push(c);
-                }
-                _ => continue,
+    fn modelize_line(&mut self, line: &Line) -> Result<(), ParseError> {
+        // Each pile takes a four characters wide column: `[A] `
+        for (i, cell) in line.columns(4).enumerate() {
+            let c = cell.text.trim().strip_prefix('[').and_then(|c| c.strip_suffix(']'));
+            if let Some(c @ 'A'..='Z') = c.and_then(|c| c.parse().ok()) {
+                // Make a crate
+                let pile = self.piles.get_mut(&(i + 1)).ok_or_else(|| cell.error("crate outside the numbered piles"))?;
+                pile.push(c);
             }
         }
+        Ok(())
     }
 
-    fn modelize_command(&mut self, line: &str) {
+    /// Parses a command, checking it against `heights`, the number of crates
+    /// on each pile once the previous commands have run.
+    fn modelize_command(&mut self, line: &Line, heights: &mut HashMap<usize, usize>) -> Result<(), ParseError> {
         // move 1 from 2 to 1
         // The first number is the number of crates to move
         // The second number is the pile to move from
         // The third number is the pile to move to
         // Parse command line to a ShipCommand
-
-        let words: Vec<&str> = line.split_whitespace().collect();
-        self.commands.push(ShipCommand::new(
-            (words[3].parse().unwrap(), words[5].parse().unwrap()),
-            words[1].parse().unwrap(),
-        ));
+        let fields = line.template("move {} from {} to {}")?;
+        let pile = |i: usize| match fields[i].parse()? {
+            0 => Err(fields[i].error("piles are numbered from 1")),
+            n if !heights.contains_key(&n) => Err(fields[i].error(format!("there is no pile {}", n))),
+            n => Ok(n),
+        };
+        let (from, to) = (pile(1)?, pile(2)?);
+        let n = fields[0].parse()?;
+        if n > heights[&from] {
+            return Err(fields[0].error(format!("pile {} only has {} crates", from, heights[&from])));
+        }
+        *heights.get_mut(&from).unwrap() -= n;
+        *heights.get_mut(&to).unwrap() += n;
+        self.commands.push(ShipCommand::new((from, to), n));
+        Ok(())
     }
 
     fn reorder_piles(&mut self) {
@@ -94,68 +145,89 @@ impl Ship {
     }
 
     fn execute_commands(&mut self) {
-        for command in self.commands.iter() {
-            let (from, to, n) = command.get_command();
-            // Clone the pile to move from
-            let mut pile = self.piles.get(&from).unwrap().clone();
-            // Clone the pile to move to
-            let mut pile_to = self.piles.get(&to).unwrap().clone();
-            // Move the crates
-            for _ in 0..n {
-                if pile.len() > 0 {
-                    pile_to.push(pile.pop().unwrap());
-                }
-            }
-            // Update the piles
-            self.piles.insert(from, pile);
-            self.piles.insert(to, pile_to);
+        for i in 0..self.commands.len() {
+            let (from, to, n) = self.commands[i].get_command();
+            self.execute_command(from, to, n);
         }
     }
 
+    /// Takes `n` crates off the top of pile `from`, bottom crate first. The
+    /// parser checks that both piles exist and that there are enough crates.
+    fn take_crates(&mut self, from: usize, n: usize) -> Vec<char> {
+        let pile = self.piles.get_mut(&from).unwrap();
+        pile.split_off(pile.len() - n)
+    }
+
+    fn execute_command(&mut self, from: usize, to: usize, n: usize) {
+        // Crates are moved one at a time, so they land in reverse order
+        let crates = self.take_crates(from, n);
+        self.piles.get_mut(&to).unwrap().extend(crates.into_iter().rev());
+        trace_event!(Day5::DAY, Debug, "command_executed", crane = 9000, from = from, to = to, crates = n);
+    }
+
     fn execute_commands_sticky(&mut self) {
-        // Execute commands but if multiple crates are moved to the same pile, they stick together and keep their order
-        for command in self.commands.iter() {
-            let (from, to, n) = command.get_command();
-            // Clone the pile to move from
-            let mut pile = self.piles.get(&from).unwrap().clone();
-            // Clone the pile to move to
-            let mut pile_to = self.piles.get(&to).unwrap().clone();
-            // Move the crates
-            if n == 1 {
-                pile_to.push(pile.pop().unwrap());
-            } else {
-                // If multiple crates are moved, we move them in the order they are in the pile but keep the order of the pile
-                // So we need to insert them in the pile in reverse order
-
-                // Get the crates to move
-                let mut crates_to_move = vec![];
-                for _ in 0..n {
-                    crates_to_move.push(pile.pop().unwrap());
-                }
-                // Insert them in the pile in reverse order
-                for c in crates_to_move.iter().rev() {
-                    pile_to.push(*c);
-                }
-            }
-            // Update the piles
-            self.piles.insert(from, pile);
-            self.piles.insert(to, pile_to);
+        for i in 0..self.commands.len() {
+            let (from, to, n) = self.commands[i].get_command();
+            self.execute_command_sticky(from, to, n);
         }
     }
 
-    fn get_crates_on_top(&self) -> String {
+    fn execute_command_sticky(&mut self, from: usize, to: usize, n: usize) {
+        // If multiple crates are moved to the same pile, they stick together and keep their order
+        let crates = self.take_crates(from, n);
+        self.piles.get_mut(&to).unwrap().extend(crates);
+        trace_event!(Day5::DAY, Debug, "command_executed", crane = 9001, from = from, to = to, crates = n);
+    }
+
+    pub fn get_crates_on_top(&self) -> String {
         // Order piles by key
         let mut piles = self.piles.iter().collect::<Vec<_>>();
         piles.sort_by(|a, b| a.0.cmp(b.0));
         // Get the crates on top
         let mut crates = String::new();
         for (_, pile) in piles.iter() {
-            crates.push(pile.last().unwrap_or(&'.').clone());
+            crates.push(*pile.last().unwrap_or(&'.'));
         }
         crates
     }
 
-    fn clone_ship(&self) -> Ship {
+    /// Piles ordered by number, bottom crate first.
+    pub fn piles(&self) -> Vec<Vec<char>> {
+        let n = self.piles.keys().max().copied().unwrap_or(0);
+        (1..=n)
+            .map(|i| self.piles.get(&i).cloned().unwrap_or_default())
+            .collect()
+    }
+
+    /// The commands as `(from, to, crates)`, in order.
+    pub fn commands(&self) -> Vec<(usize, usize, usize)> {
+        self.commands.iter().map(|c| c.get_command()).collect()
+    }
+
+    /// Runs a single `(from, to, crates)` command. `sticky` selects the part 2
+    /// crane. Only the parser checks commands, so one the piles cannot take
+    /// panics.
+    pub fn apply(&mut self, command: (usize, usize, usize), sticky: bool) {
+        let (from, to, n) = command;
+        if sticky {
+            self.execute_command_sticky(from, to, n);
+        } else {
+            self.execute_command(from, to, n);
+        }
+    }
+
+    /// The piles before the first command and after each of them.
+    pub fn states(&self, sticky: bool) -> Vec<Vec<Vec<char>>> {
+        let mut ship = self.clone_ship();
+        let mut states = vec![ship.piles()];
+        for command in self.commands() {
+            ship.apply(command, sticky);
+            states.push(ship.piles());
+        }
+        states
+    }
+
+    pub fn clone_ship(&self) -> Ship {
         let mut ret = Ship::new();
         for (i, pile) in self.piles.iter() {
             ret.piles.insert(*i, pile.clone());
@@ -167,38 +239,55 @@ impl Ship {
     }
 }
 
-#[aoc_generator(day5)]
-fn input_generator(input: &str) -> Ship {
-    let mut ship = Ship::new();
-    let mut commands_mode = false;
-    for l in input.lines() {
-        if l == "" {
-            commands_mode = true;
-            continue;
-        }
-        if commands_mode {
-            ship.modelize_command(l);
-        } else {
-            ship.modelize_line(l);
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+    type Input = Ship;
+    type Output1 = String;
+    type Output2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let mut ship = Ship::new();
+        let mut lines = aoc_common::lines(input);
+        let drawing = lines.by_ref().take_while(|l| !l.is_blank()).collect::<Vec<_>>();
+        ship.modelize_drawing(&drawing)?;
+        let mut heights = ship.piles.iter().map(|(&i, pile)| (i, pile.len())).collect();
+        for l in lines.filter(|l| !l.is_blank()) {
+            ship.modelize_command(&l, &mut heights)?;
         }
+        // Piles were read from the top
+        ship.reorder_piles();
+        trace_event!(Day5::DAY, Info, "ship_loaded", piles = ship.piles.len(), commands = ship.commands.len());
+        Ok(ship)
+    }
+
+    fn part1(input: &Self::Input) -> String {
+        let mut ship = input.clone_ship();
+        ship.execute_commands();
+        ship.get_crates_on_top()
+    }
+
+    fn part2(input: &Self::Input) -> String {
+        let mut ship = input.clone_ship();
+        ship.execute_commands_sticky();
+        ship.get_crates_on_top()
     }
-    ship
+}
+
+#[aoc_generator(day5)]
+pub fn input_generator(input: &str) -> Result<Ship, PuzzleError> {
+    error::parse::<Day5>(input)
 }
 
 #[aoc(day5, part1)]
-fn solve_part1(input: &Ship) -> String {
-    let mut ship = input.clone_ship();
-    ship.reorder_piles();
-    ship.execute_commands();
-    ship.get_crates_on_top()
+pub fn solve_part1(input: &Ship) -> String {
+    Day5::part1(input)
 }
 
 #[aoc(day5, part2)]
-fn solve_part2(input: &Ship) -> String {
-    let mut ship = input.clone_ship();
-    ship.reorder_piles();
-    ship.execute_commands_sticky();
-    ship.get_crates_on_top()
+pub fn solve_part2(input: &Ship) -> String {
+    Day5::part2(input)
 }
 
 #[cfg(test)]
@@ -216,7 +305,7 @@ move 1 from 2 to 1
 move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
-        assert_eq!(solve_part1(&input_generator(input)), "CMZ".to_string());
+        assert_eq!(solve_part1(&input_generator(input).unwrap()), "CMZ".to_string());
     }
 
     #[test]
@@ -230,6 +319,44 @@ move 1 from 2 to 1
 move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
-        assert_eq!(solve_part2(&input_generator(input)), "MCD".to_string());
+        assert_eq!(solve_part2(&input_generator(input).unwrap()), "MCD".to_string());
+    }
+
+    #[test]
+    fn test_input_generator_errors() {
+        let input = "[A]\n 1   2 \n\nmove 1 from 1 to 2\nmove 1 frm 1 to 2";
+        let err = input_generator(input).unwrap_err();
+        assert_eq!((err.line, err.column), (5, 6));
+        let err = input_generator("[A]\n 1\n\nmove 1 from 0 to 1").unwrap_err();
+        assert_eq!((err.line, err.column, err.message.as_str()), (4, 13, "piles are numbered from 1"));
+    }
+
+    #[test]
+    fn test_missing_and_short_piles() {
+        // Found by fuzzing: moving to a pile that was empty in the drawing, or
+        // more crates than a pile holds, used to panic
+        let ship = input_generator("[A]\n 1   2   3\n\nmove 1 from 1 to 2").unwrap();
+        assert_eq!((solve_part1(&ship), solve_part2(&ship)), (String::from(".A."), String::from(".A.")));
+        let err = |input: &str| {
+            let err = input_generator(input).unwrap_err();
+            (err.line, err.column, err.message)
+        };
+        let two = "[A]\n[B]\n 1   2\n\n";
+        assert_eq!(err(&format!("{}move 3 from 1 to 2", two)), (5, 6, String::from("pile 1 only has 2 crates")));
+        assert_eq!(
+            err(&format!("{}move 2 from 1 to 2\nmove 1 from 1 to 2", two)),
+            (6, 6, String::from("pile 1 only has 0 crates"))
+        );
+        assert_eq!(err(&format!("{}move 1 from 3 to 1", two)), (5, 13, String::from("there is no pile 3")));
+        assert_eq!(
+            err(&format!("{}move 1 from 1 to 99999999999", two)),
+            (5, 18, String::from("there is no pile 99999999999"))
+        );
+        assert_eq!(err("[A] [B]\n 1\n\n"), (1, 5, String::from("crate outside the numbered piles")));
+        assert_eq!(err("[A]\n 1 1\n\n"), (2, 4, String::from("pile 1 is numbered twice")));
+        // A pile onto itself: the first crane reverses the crates it lifts
+        let ship = input_generator("[A]\n[B]\n[C]\n 1\n\nmove 2 from 1 to 1").unwrap();
+        assert_eq!(ship.states(false).last().unwrap(), &[vec!['C', 'A', 'B']]);
+        assert_eq!(ship.states(true).last().unwrap(), &[vec!['C', 'B', 'A']]);
     }
 }
@@ -1,4 +1,9 @@
-use aoc_runner_derive::aoc;
+use aoc_runner_derive::{aoc, aoc_generator};
+
+use crate::aoc_common::{self, ParseError};
+use crate::error::{self, PuzzleError};
+use crate::solution::Solution;
+use crate::trace_event;
 
 struct ElfDevice {
     buffer: Vec<char>,
@@ -24,9 +29,10 @@ impl ElfDevice {
                 self.buffer.push(c);
             } else {
                 // No, then is the buffer full?
-                if self.buffer.len() == marker_length - 1 {
+                if Some(self.buffer.len()) == marker_length.checked_sub(1) {
                     // Yes, we have a packet!
                     ret = i + 1;
+                    trace_event!(Day6::DAY, Info, "marker_found", length = marker_length, position = ret);
                     break;
                 } else {
                     // No, then push c onto the end of the buffer
@@ -38,44 +44,127 @@ impl ElfDevice {
     }
 }
 
+/// Number of characters processed when the first `marker_length` distinct
+/// characters end, or 0 if the signal has no marker or `marker_length` is 0.
+pub fn marker_position(signal: &str, marker_length: usize) -> usize {
+    ElfDevice::new().find_marker(signal, marker_length)
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+    type Input = String;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let mut lines = aoc_common::lines(input).filter(|l| !l.is_blank());
+        let line = match lines.next() {
+            Some(l) => l,
+            None => return Ok(String::new()),
+        };
+        let signal = line.trimmed();
+        if let Some(i) = signal.text.find(|c: char| !c.is_ascii_lowercase()) {
+            return Err(line.error(signal.column + i, "expected a lowercase letter"));
+        }
+        if let Some(l) = lines.next() {
+            return Err(l.error(1, "expected a single line signal"));
+        }
+        Ok(signal.text.to_string())
+    }
+
+    fn part1(input: &Self::Input) -> usize {
+        // The start of a packet is indicated by a sequence of four characters that are all different
+        // How many characters need to be processed before the first start-of-packet marker is detected?
+        // Find a sequence of four characters that are all different
+        // The first character is at index 0
+        let mut elf_device = ElfDevice::new();
+        elf_device.find_marker(input, 4)
+    }
+
+    fn part2(input: &Self::Input) -> usize {
+        // The start of a packet is indicated by a sequence of four characters that are all different
+        // How many characters need to be processed before the first start-of-packet marker is detected?
+        // Find a sequence of four characters that are all different
+        // The first character is at index 0
+        let mut elf_device = ElfDevice::new();
+        elf_device.find_marker(input, 14)
+    }
+}
+
+#[aoc_generator(day6)]
+pub fn input_generator(input: &str) -> Result<String, PuzzleError> {
+    error::parse::<Day6>(input)
+}
+
 #[aoc(day6, part1)]
-fn solve_day6_part1(input: &str) -> usize {
-    // The start of a packet is indicated by a sequence of four characters that are all different
-    // How many characters need to be processed before the first start-of-packet marker is detected?
-    // Find a sequence of four characters that are all different
-    // The first character is at index 0
-    let mut elf_device = ElfDevice::new();
-    elf_device.find_marker(input, 4)
+pub fn solve_day6_part1(input: &String) -> usize {
+    Day6::part1(input)
 }
 
 #[aoc(day6, part2)]
-fn solve_day6_part2(input: &str) -> usize {
-    // The start of a packet is indicated by a sequence of four characters that are all different
-    // How many characters need to be processed before the first start-of-packet marker is detected?
-    // Find a sequence of four characters that are all different
-    // The first character is at index 0
-    let mut elf_device = ElfDevice::new();
-    elf_device.find_marker(input, 14)
+pub fn solve_day6_part2(input: &String) -> usize {
+    Day6::part2(input)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_part1() {
-        assert_eq!(solve_day6_part1("bvwbjplbgvbhsrlpgdmjqwftvncz"), 5);
-        assert_eq!(solve_day6_part1("nppdvjthqldpwncqszvftbrmjlhg"), 6);
-        assert_eq!(solve_day6_part1("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), 10);
-        assert_eq!(solve_day6_part1("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), 11);
+        assert_eq!(solve_day6_part1(&input_generator("bvwbjplbgvbhsrlpgdmjqwftvncz").unwrap()), 5);
+        assert_eq!(solve_day6_part1(&input_generator("nppdvjthqldpwncqszvftbrmjlhg").unwrap()), 6);
+        assert_eq!(solve_day6_part1(&input_generator("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").unwrap()), 10);
+        assert_eq!(solve_day6_part1(&input_generator("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").unwrap()), 11);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(solve_day6_part2("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), 19);
-        assert_eq!(solve_day6_part2("bvwbjplbgvbhsrlpgdmjqwftvncz"), 23);
-        assert_eq!(solve_day6_part2("nppdvjthqldpwncqszvftbrmjlhg"), 23);
-        assert_eq!(solve_day6_part2("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), 29);
-        assert_eq!(solve_day6_part2("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), 26);
+        assert_eq!(solve_day6_part2(&input_generator("mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap()), 19);
+        assert_eq!(solve_day6_part2(&input_generator("bvwbjplbgvbhsrlpgdmjqwftvncz").unwrap()), 23);
+        assert_eq!(solve_day6_part2(&input_generator("nppdvjthqldpwncqszvftbrmjlhg").unwrap()), 23);
+        assert_eq!(solve_day6_part2(&input_generator("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").unwrap()), 29);
+        assert_eq!(solve_day6_part2(&input_generator("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").unwrap()), 26);
+    }
+
+    #[test]
+    fn test_input_generator_errors() {
+        let err = input_generator("mjqjpqmgbljs7hdztnvjfqwrcgsmlb").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 13));
+        let err = input_generator("mjqjpqmgbljs\nhdztnvjfqwrcgsmlb").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn test_zero_length_marker() {
+        assert_eq!(marker_position("abc", 0), 0);
+        assert_eq!(marker_position("", 0), 0);
+    }
+
+    /// Checks every window, returning 0 when there is no marker like
+    /// `find_marker` does.
+    fn find_marker_naive(sequence: &str, marker_length: usize) -> usize {
+        let chars = sequence.chars().collect::<Vec<_>>();
+        chars
+            .windows(marker_length)
+            .position(|w| (1..w.len()).all(|i| !w[..i].contains(&w[i])))
+            .map_or(0, |i| i + marker_length)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_find_marker_packet(sequence in "[a-f]{0,40}") {
+            let mut elf_device = ElfDevice::new();
+            prop_assert_eq!(elf_device.find_marker(&sequence, 4), find_marker_naive(&sequence, 4));
+        }
+
+        #[test]
+        fn prop_find_marker_message(sequence in "[a-p]{0,80}") {
+            let mut elf_device = ElfDevice::new();
+            prop_assert_eq!(elf_device.find_marker(&sequence, 14), find_marker_naive(&sequence, 14));
+        }
     }
 }
@@ -1,74 +1,519 @@
-use std::{collections::HashMap, hash::Hash};
+use std::collections::{HashMap, HashSet};
+
+use aoc_runner_derive::{aoc, aoc_generator};
+
+use crate::aoc_common::{self, Field, Line, ParseError};
+use crate::error::{self, PuzzleError};
+use crate::solution::Solution;
+use crate::trace_event;
+
+pub mod diff;
+pub mod materialize;
+pub mod shell;
+
+pub const DISK_SIZE: u32 = 70000000;
+/// Free space the update needs.
+pub const UPDATE_SIZE: u32 = 30000000;
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct File {
     name: String,
     size: u32,
 }
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Directory {
     name: String,
     files: Vec<File>,
     subdirs: Vec<Directory>,
+    listed: bool,
 }
-struct FileSystem {
+
+impl Directory {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            files: Vec::new(),
+            subdirs: Vec::new(),
+            listed: false,
+        }
+    }
+}
+
+/// Subdirectory names and `(name, size)` of files, as listed by `ls`.
+pub type Listing<'a> = (Vec<&'a str>, Vec<(&'a str, u32)>);
+
+/// What to do when a directory is listed again and the listing does not
+/// match what is already known about it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Relisting {
+    /// Keep the latest listing, reporting each difference as a warning.
+    #[default]
+    Warn,
+    /// Fail on the first difference.
+    Reject,
+}
+
+/// A directory listing being read from the transcript.
+struct PendingListing<'a> {
+    command: Line<'a>,
+    dir: String,
+    dirs: Vec<Field<'a>>,
+    files: Vec<(Field<'a>, u32)>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "FileSystemData"))]
+pub struct FileSystem {
+    // Only meaningful while parsing a transcript
+    #[cfg_attr(feature = "serde", serde(skip))]
     pwd: Vec<String>,
     tree: HashMap<String, Directory>,
 }
 
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct FileSystemData {
+    tree: HashMap<String, Directory>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<FileSystemData> for FileSystem {
+    type Error = String;
+
+    fn try_from(data: FileSystemData) -> Result<Self, String> {
+        if !data.tree.contains_key("/") {
+            return Err(String::from("missing root directory"));
+        }
+        for (path, dir) in data.tree.iter() {
+            let parent = match FileSystem::split_path(path) {
+                Some((parent, name)) if is_valid_name(name) => parent,
+                _ if path == "/" => "/",
+                _ => return Err(format!("{} is not a valid directory path", path)),
+            };
+            if !path.ends_with('/') || !data.tree.contains_key(parent) {
+                return Err(format!("{} is not a directory path under an existing parent", path));
+            }
+            let names = dir.files.iter().map(|f| &f.name).chain(dir.subdirs.iter().map(|d| &d.name));
+            if let Some(name) = names.into_iter().find(|name| !is_valid_name(name)) {
+                return Err(format!("invalid name {:?} in {}", name, path));
+            }
+            if let Some(sub) = dir.subdirs.iter().find(|d| !data.tree.contains_key(&format!("{}{}/", path, d.name))) {
+                return Err(format!("{}{}/ is listed but missing", path, sub.name));
+            }
+        }
+        let fs = Self {
+            pwd: vec![String::from("/")],
+            tree: data.tree,
+        };
+        match fs.total_size() > u32::MAX as u64 {
+            true => Err(format!("total size exceeds {}", u32::MAX)),
+            false => Ok(fs),
+        }
+    }
+}
+
+/// Whether `name` can name a file or directory: neither empty, `.` nor `..`,
+/// without a `/` and without surrounding whitespace.
+fn is_valid_name(name: &str) -> bool {
+    !matches!(name, "" | "." | "..") && !name.contains('/') && name.trim() == name
+}
+
+/// Splits `/a/e/` into `["/", "a/", "e/"]`.
+fn components(path: &str) -> Vec<String> {
+    std::iter::once("/")
+        .chain(path[1..].split_inclusive('/'))
+        .map(String::from)
+        .collect()
+}
+
+/// Resolves `path` against the directory `cwd`, such as `/a/`, as a
+/// directory path such as `/a/e/`. `..` stops at the root.
+fn resolve(cwd: &str, path: &str) -> String {
+    let mut ret = match path.starts_with('/') {
+        true => vec![String::from("/")],
+        false => components(cwd),
+    };
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if ret.len() > 1 {
+                    ret.pop();
+                }
+            }
+            _ => ret.push(format!("{}/", part)),
+        }
+    }
+    ret.concat()
+}
+
 impl FileSystem {
     fn new() -> Self {
         let pwd = vec![String::from("/")];
         let mut tree = HashMap::new();
-        tree.insert(
-            pwd.join(""),
-            Directory {
-                name: String::from("/"),
-                files: Vec::new(),
-                subdirs: Vec::new(),
-            },
-        );
+        tree.insert(pwd.join(""), Directory::new(String::from("/")));
         Self { pwd, tree }
     }
-    fn mkdir(&mut self, dir_name: &str) {
-        let path = format!("{}{}/", self.pwd.join(""), dir_name);
-        self.tree.insert(
-            path,
-            Directory {
-                name: format!("{}/", dir_name),
-                files: Vec::new(),
-                subdirs: Vec::new(),
-            },
+
+    /// Rebuilds the file system from a transcript which, besides `cd` and
+    /// `ls`, may run `mkdir <path>`, `rm <path>` and `mv <from> <to>` with
+    /// paths relative to the working directory. Listing a directory again
+    /// replaces what was known about it; differences are handled according
+    /// to `relisting`, and returned in [`Relisting::Warn`] mode.
+    pub fn parse_with(input: &str, relisting: Relisting) -> Result<(Self, Vec<ParseError>), ParseError> {
+        let mut fs = FileSystem::new();
+        let mut warnings = vec![];
+        let mut listing: Option<PendingListing> = None;
+        for line in aoc_common::lines(input) {
+            if !line.text.starts_with('$') {
+                let Some(listing) = listing.as_mut() else {
+                    return Err(line.error(1, "output outside of a listing"));
+                };
+                if line.text.starts_with("dir ") {
+                    listing.dirs.push(Self::entry_name(line.template("dir {}")?[0])?);
+                } else {
+                    let (size, name) = line.key_value(" ")?;
+                    listing.files.push((Self::entry_name(name)?, size.parse()?));
+                }
+                continue;
+            }
+            if let Some(done) = listing.take() {
+                fs.apply_listing(done, relisting, &mut warnings)?;
+            }
+            let words = line.words().collect::<Vec<_>>();
+            let texts = words.iter().map(|w| w.text).collect::<Vec<_>>();
+            match texts.as_slice() {
+                ["$", "ls"] => {
+                    listing = Some(PendingListing {
+                        command: line,
+                        dir: fs.pwd.concat(),
+                        dirs: vec![],
+                        files: vec![],
+                    })
+                }
+                ["$", "cd", path] => fs.change_directory(path),
+                ["$", "mkdir", path] => {
+                    if !fs.create_dir(&fs.resolve(path)) {
+                        return Err(words[2].error(format!("cannot create directory {}", path)));
+                    }
+                }
+                ["$", "rm", path] => {
+                    let target = fs.find(path).ok_or_else(|| words[2].error(format!("{} does not exist", path)))?;
+                    if !fs.remove(&target) {
+                        return Err(words[2].error(format!("cannot remove {}", path)));
+                    }
+                }
+                ["$", "mv", from, to] => {
+                    let source = fs.find(from).ok_or_else(|| words[2].error(format!("{} does not exist", from)))?;
+                    let mut target = fs.resolve(to);
+                    if fs.is_dir(&target) {
+                        // Into an existing directory, keeping the name
+                        let Some((_, name)) = Self::split_path(&source) else {
+                            return Err(words[2].error(format!("cannot move {}", from)));
+                        };
+                        target.push_str(name);
+                    } else {
+                        target.pop();
+                    }
+                    if source.ends_with('/') {
+                        target.push('/');
+                    }
+                    if !fs.rename(&source, &target) {
+                        return Err(words[3].error(format!("cannot move {} to {}", from, to)));
+                    }
+                }
+                _ => return Err(line.error(1, format!("unknown command {:?}", line.text))),
+            }
+        }
+        if let Some(done) = listing {
+            fs.apply_listing(done, relisting, &mut warnings)?;
+        }
+        Ok((fs, warnings))
+    }
+
+    fn entry_name(field: Field) -> Result<Field, ParseError> {
+        match is_valid_name(field.text) {
+            true => Ok(field),
+            false => Err(field.error(format!("invalid name {:?}", field.text))),
+        }
+    }
+
+    /// Checks a listing against what is already known about the directory,
+    /// then makes the directory match it.
+    fn apply_listing(
+        &mut self,
+        listing: PendingListing,
+        relisting: Relisting,
+        warnings: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
+        let PendingListing {
+            command,
+            dir,
+            dirs,
+            files,
+        } = listing;
+        let Some(known) = self.tree.get(&dir) else {
+            return Err(command.error(1, format!("{} does not exist", dir)));
+        };
+        let check = known.listed || !known.files.is_empty() || !known.subdirs.is_empty();
+        let mut changes = vec![];
+        let mut seen = HashSet::new();
+        for name in dirs.iter() {
+            if !seen.insert(name.text) {
+                changes.push(name.error(format!("{} is listed twice", name.text)));
+            } else if check && !known.subdirs.iter().any(|d| d.name == name.text) {
+                changes.push(name.error(format!("new directory {}{}/", dir, name.text)));
+            }
+        }
+        for (name, size) in files.iter() {
+            if !seen.insert(name.text) {
+                changes.push(name.error(format!("{} is listed twice", name.text)));
+            } else if check {
+                match known.files.iter().find(|f| f.name == name.text) {
+                    None => changes.push(name.error(format!("new file {}{}", dir, name.text))),
+                    Some(f) if f.size != *size => changes.push(name.error(format!(
+                        "{}{} changed size from {} to {}",
+                        dir, name.text, f.size, size
+                    ))),
+                    Some(_) => {}
+                }
+            }
+        }
+        let gone_dirs = known
+            .subdirs
+            .iter()
+            .filter(|d| !dirs.iter().any(|name| name.text == d.name))
+            .map(|d| format!("{}{}/", dir, d.name))
+            .collect::<Vec<_>>();
+        let gone_files = known
+            .files
+            .iter()
+            .filter(|f| !files.iter().any(|(name, _)| name.text == f.name))
+            .map(|f| format!("{}{}", dir, f.name));
+        changes.extend(
+            gone_dirs
+                .iter()
+                .cloned()
+                .chain(gone_files)
+                .map(|path| command.error(1, format!("{} is gone", path))),
         );
+
+        for change in changes {
+            match relisting {
+                Relisting::Reject => return Err(change),
+                Relisting::Warn => {
+                    trace_event!(Day7::DAY, Warn, "listing_changed", dir = dir, change = change.message);
+                    warnings.push(change);
+                }
+            }
+        }
+
+        for path in gone_dirs {
+            self.remove(&path);
+        }
+        let known = self.tree.get_mut(&dir).unwrap();
+        known.listed = true;
+        known.files.clear();
+        // Duplicates are already reported, the first entry wins
+        for name in dirs {
+            self.create_dir(&format!("{}{}/", dir, name.text));
+        }
+        // Sizes are u32, so the root's total must fit
+        let mut total = self.total_size();
+        for (name, size) in files {
+            if self.create_file(&format!("{}{}", dir, name.text), size) {
+                total += size as u64;
+                if total > u32::MAX as u64 {
+                    return Err(name.error(format!("total size exceeds {}", u32::MAX)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn change_directory(&mut self, path: &str) {
+        self.pwd = components(&self.resolve(path));
+        trace_event!(Day7::DAY, Debug, "directory_entered", path = self.pwd.join(""));
+    }
+
+    /// Resolves `path` against the working directory of the transcript.
+    fn resolve(&self, path: &str) -> String {
+        resolve(&self.pwd.concat(), path)
+    }
+
+    fn find(&self, path: &str) -> Option<String> {
+        self.find_from(&self.pwd.concat(), path)
+    }
+
+    /// The directory or file `path` refers to from the directory `cwd`, in
+    /// the form taken by [`FileSystem::remove`].
+    fn find_from(&self, cwd: &str, path: &str) -> Option<String> {
+        let mut ret = resolve(cwd, path);
+        if self.is_dir(&ret) {
+            return Some(ret);
+        }
+        ret.pop();
+        self.file_size(&ret).map(|_| ret)
+    }
+
+    fn total_size(&self) -> u64 {
         self.tree
-            .get_mut(&self.pwd.join(""))
-            .unwrap()
-            .subdirs
-            .push(Directory {
-                name: String::from(dir_name),
-                files: Vec::new(),
-                subdirs: Vec::new(),
-            });
+            .values()
+            .flat_map(|d| d.files.iter())
+            .map(|f| f.size as u64)
+            .sum()
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        let base = path.strip_suffix('/').unwrap_or(path);
+        self.is_dir(&format!("{}/", base)) || self.file_size(base).is_some()
     }
-    fn touch(&mut self, file_name: &str, file_size: u32) {
+
+    /// Creates the empty directory `path`, ending with `/`, in an existing
+    /// directory. Returns whether it was created.
+    pub fn create_dir(&mut self, path: &str) -> bool {
+        let Some((parent, name)) = Self::split_path(path) else {
+            return false;
+        };
+        if !path.ends_with('/') || !self.is_dir(parent) || self.exists(path) {
+            return false;
+        }
+        trace_event!(Day7::DAY, Debug, "directory_created", path = path);
+        self.tree.insert(path.to_string(), Directory::new(format!("{}/", name)));
         self.tree
-            .get_mut(&self.pwd.join(""))
+            .get_mut(parent)
             .unwrap()
-            .files
-            .push(File {
-                name: String::from(file_name),
-                size: file_size,
+            .subdirs
+            .push(Directory::new(name.to_string()));
+        true
+    }
+
+    /// Creates the file `path` in an existing directory. Returns whether it
+    /// was created.
+    pub fn create_file(&mut self, path: &str, size: u32) -> bool {
+        let Some((parent, name)) = Self::split_path(path) else {
+            return false;
+        };
+        if path.ends_with('/') || !self.is_dir(parent) || self.exists(path) {
+            return false;
+        }
+        trace_event!(Day7::DAY, Trace, "file_created", dir = parent, name = name, size = size);
+        self.tree.get_mut(parent).unwrap().files.push(File {
+            name: name.to_string(),
+            size,
+        });
+        true
+    }
+
+    /// Moves the file or directory `from` to `to`, both in the form taken by
+    /// [`FileSystem::remove`]. The destination must not exist but its parent
+    /// must, and a directory cannot be moved into itself. Returns whether
+    /// anything moved.
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        let (Some((from_parent, from_name)), Some((to_parent, to_name))) =
+            (Self::split_path(from), Self::split_path(to))
+        else {
+            return false;
+        };
+        if from.ends_with('/') != to.ends_with('/') || !self.is_dir(to_parent) || self.exists(to) {
+            return false;
+        }
+        if from.ends_with('/') {
+            if !self.is_dir(from) || to.starts_with(from) {
+                return false;
+            }
+            let moved = self
+                .tree
+                .keys()
+                .filter(|p| p.starts_with(from))
+                .cloned()
+                .collect::<Vec<_>>();
+            for path in moved {
+                let dir = self.tree.remove(&path).unwrap();
+                self.tree.insert(format!("{}{}", to, &path[from.len()..]), dir);
+            }
+            self.tree.get_mut(to).unwrap().name = format!("{}/", to_name);
+            let from_parent = self.tree.get_mut(from_parent).unwrap();
+            from_parent.subdirs.retain(|d| d.name != from_name);
+            let to_parent = self.tree.get_mut(to_parent).unwrap();
+            to_parent.subdirs.push(Directory::new(to_name.to_string()));
+            // The working directory moves along
+            if let Some(rest) = self.pwd.concat().strip_prefix(from) {
+                self.pwd = components(&format!("{}{}", to, rest));
+            }
+        } else {
+            let Some(size) = self.file_size(from) else {
+                return false;
+            };
+            let from_parent = self.tree.get_mut(from_parent).unwrap();
+            from_parent.files.retain(|f| f.name != from_name);
+            self.tree.get_mut(to_parent).unwrap().files.push(File {
+                name: to_name.to_string(),
+                size,
             });
+        }
+        true
     }
-    fn change_directory(&mut self, path: &str) {
-        match path {
-            "/" => self.pwd = vec![String::from("/")],
-            ".." => {
-                self.pwd.pop();
+
+    pub fn is_dir(&self, path: &str) -> bool {
+        self.tree.contains_key(path)
+    }
+
+    /// The contents of the directory at `path`, or `None` if there is no such
+    /// directory.
+    pub fn list(&self, path: &str) -> Option<Listing<'_>> {
+        let dir = self.tree.get(path)?;
+        Some((
+            dir.subdirs.iter().map(|d| d.name.as_str()).collect(),
+            dir.files.iter().map(|f| (f.name.as_str(), f.size)).collect(),
+        ))
+    }
+
+    /// Splits `/a/b.txt` or `/a/b/` into `/a/` and `b.txt` or `b`.
+    fn split_path(path: &str) -> Option<(&str, &str)> {
+        let trimmed = path.strip_suffix('/').unwrap_or(path);
+        let i = trimmed.rfind('/')?;
+        Some((&trimmed[..=i], &trimmed[i + 1..])).filter(|(_, name)| !name.is_empty())
+    }
+
+    pub fn file_size(&self, path: &str) -> Option<u32> {
+        let (parent, name) = Self::split_path(path)?;
+        let dir = self.tree.get(parent)?;
+        dir.files.iter().find(|f| f.name == name).map(|f| f.size)
+    }
+
+    /// Removes the file, or the directory and everything under it, at `path`
+    /// (directories end with `/`). Returns whether there was one; the root
+    /// cannot be removed.
+    pub fn remove(&mut self, path: &str) -> bool {
+        let Some((parent, name)) = Self::split_path(path) else {
+            return false;
+        };
+        if path.ends_with('/') {
+            if !self.tree.contains_key(path) {
+                return false;
+            }
+            self.tree.retain(|p, _| !p.starts_with(path));
+            if let Some(dir) = self.tree.get_mut(parent) {
+                dir.subdirs.retain(|d| d.name != name);
+            }
+            true
+        } else {
+            match self.tree.get_mut(parent) {
+                Some(dir) => {
+                    let before = dir.files.len();
+                    dir.files.retain(|f| f.name != name);
+                    dir.files.len() != before
+                }
+                None => false,
             }
-            _ => self.pwd.push(format!("{}/", path)),
         }
     }
-    fn get_directory_size(&self, path: &str) -> u32 {
+
+    pub fn get_directory_size(&self, path: &str) -> u32 {
         // Get the size of this directory
         // Sum the size of every file in this directory and in every subdirectory
         let mut size = self
@@ -84,76 +529,75 @@ impl FileSystem {
         }
         size
     }
-    fn print_tree(&self) {
-        // This function is just used to print the tree for debugging
-        fn print_tree_helper<T: Hash + Eq + std::fmt::Display>(
-            tree: &HashMap<T, Directory>,
-            path: &str,
-        ) {
-            for (k, dir) in tree.iter() {
-                println!("{}{}: {:?}", path, k, dir);
-                print_tree_helper(
-                    &dir.subdirs
-                        .iter()
-                        .cloned()
-                        .map(|d| (d.name.clone(), d))
-                        .collect(),
-                    &format!("{}{}/", path, dir.name),
-                );
+    /// The size of every directory, keyed by path, in a single pass: the files
+    /// of each directory are added to the directory and to all its ancestors.
+    pub fn directory_sizes(&self) -> HashMap<String, u32> {
+        let mut sizes = HashMap::with_capacity(self.tree.len());
+        for (path, dir) in self.tree.iter() {
+            let size = dir.files.iter().map(|file| file.size).sum::<u32>();
+            for (i, _) in path.match_indices('/') {
+                *sizes.entry(path[..=i].to_string()).or_insert(0) += size;
             }
         }
-        print_tree_helper(&self.tree, "");
+        sizes
     }
 }
 
-use aoc_runner_derive::{aoc, aoc_generator};
+pub struct Day7;
 
-#[aoc_generator(day7)]
-fn input_generator(input: &str) -> FileSystem {
-    let mut fs = FileSystem::new();
-    for line in input.lines() {
-        match &line[0..4] {
-            "$ cd" => fs.change_directory(&line[5..]),
-            "$ ls" => {}
-            "dir " => fs.mkdir(&line[4..]),
-            _ => {
-                let mut iter = line.split_whitespace();
-                let file_size = iter.next().unwrap().parse::<u32>().unwrap();
-                let file_name = iter.next().unwrap();
-                fs.touch(file_name, file_size);
-            }
-        }
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+    type Input = FileSystem;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    /// Reads the transcript in [`Relisting::Warn`] mode. Its warnings only
+    /// show up as trace events here; [`FileSystem::parse_with`] is the only
+    /// way to get them back.
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        FileSystem::parse_with(input, Relisting::Warn).map(|(fs, _)| fs)
+    }
+
+    fn part1(fs: &Self::Input) -> u32 {
+        // Get a vector of directories
+        fs.directory_sizes()
+            .into_values()
+            .filter(|size| *size < 100000)
+            .sum()
+    }
+
+    fn part2(fs: &Self::Input) -> u32 {
+        let sizes = fs.directory_sizes();
+        let unused_space = DISK_SIZE.saturating_sub(sizes["/"]);
+        // The root always qualifies: it holds either all of the used space or
+        // more than the update needs
+        sizes
+            .into_values()
+            .filter(|size| *size as u64 + unused_space as u64 >= UPDATE_SIZE as u64)
+            .min()
+            .unwrap()
     }
-    println!("Cleaning tree...");
-    fs
+}
+
+#[aoc_generator(day7)]
+pub fn input_generator(input: &str) -> Result<FileSystem, PuzzleError> {
+    error::parse::<Day7>(input)
 }
 
 #[aoc(day7, part1)]
-fn solve_part1(fs: &FileSystem) -> u32 {
-    // Print tree
-    fs.print_tree();
-    // Get a vector of directories
-    fs.tree
-        .iter()
-        .map(|(k, _)| fs.get_directory_size(k))
-        .filter(|size| *size < 100000)
-        .sum()
+pub fn solve_part1(fs: &FileSystem) -> u32 {
+    Day7::part1(fs)
 }
 
 #[aoc(day7, part2)]
-fn solve_part2(fs: &FileSystem) -> u32 {
-    let unused_space = 70000000 - fs.get_directory_size("/");
-    fs.tree
-        .iter()
-        .map(|(k, _)| fs.get_directory_size(k))
-        .filter(|size| *size + unused_space >= 30000000)
-        .min()
-        .unwrap()
+pub fn solve_part2(fs: &FileSystem) -> u32 {
+    Day7::part2(fs)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::{prelude::*, sample::Index};
 
     #[test]
     fn test_part1() {
@@ -180,7 +624,7 @@ $ ls
 8033020 d.log
 5626152 d.ext
 7214296 k";
-        assert_eq!(solve_part1(&input_generator(input)), 95437);
+        assert_eq!(solve_part1(&input_generator(input).unwrap()), 95437);
     }
 
     #[test]
@@ -208,6 +652,180 @@ $ ls
 8033020 d.log
 5626152 d.ext
 7214296 k";
-        assert_eq!(solve_part2(&input_generator(input)), 24933642);
+        assert_eq!(solve_part2(&input_generator(input).unwrap()), 24933642);
+    }
+
+    #[test]
+    fn test_trace_events() {
+        let input = "$ cd /\n$ ls\ndir a\n14848514 b.txt\n$ cd a\n$ ls\n584 i";
+        let filter = crate::trace::Filter::parse("7=debug").unwrap();
+        let (_, events) = crate::trace::capture(filter, || input_generator(input).unwrap());
+        let events = events.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [
+                r#"[day 7] DEBUG directory_entered path="/""#,
+                r#"[day 7] DEBUG directory_created path="/a/""#,
+                r#"[day 7] DEBUG directory_entered path="/a/""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_input_generator_errors() {
+        let err = input_generator("$ cd /\n$ ls\n12ab c.txt").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+        let err = input_generator("$ cd /\n$\nx").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    fn sizes(fs: &FileSystem) -> Vec<(String, u32)> {
+        let mut sizes = fs.directory_sizes().into_iter().collect::<Vec<_>>();
+        sizes.sort();
+        sizes
+    }
+
+    #[test]
+    fn test_large_sizes() {
+        // Found by fuzzing: sizes used to overflow, and part 2 to underflow on
+        // a disk fuller than its capacity
+        let err = input_generator("$ cd /\n$ ls\ndir a\n4000000000 b\n$ cd a\n$ ls\n400000000 c").unwrap_err();
+        assert_eq!((err.line, err.column), (7, 11));
+        let fs = input_generator("$ cd /\n$ ls\ndir a\n80000000 b\n$ cd a\n$ ls\n4000000000 c").unwrap();
+        assert_eq!(solve_part2(&fs), 4000000000);
+    }
+
+    #[test]
+    fn test_transcript_commands() {
+        let input = "$ cd /
+$ ls
+dir a
+10 b
+$ mkdir a/e
+$ cd a
+$ mkdir /c
+$ mv /b e
+$ mv ../c x
+$ cd x
+$ ls
+3 y
+$ cd /
+$ mv a/x/y a/z
+$ rm a/e
+$ mv a/x/ a/w";
+        let (fs, warnings) = FileSystem::parse_with(input, Relisting::Reject).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(sizes(&fs), [(String::from("/"), 3), (String::from("/a/"), 3), (String::from("/a/w/"), 0)]);
+        assert_eq!(fs.file_size("/a/z"), Some(3));
+        assert_eq!(fs.list("/a/").unwrap(), (vec!["w"], vec![("z", 3)]));
+
+        let err = |input: &str| {
+            let err = FileSystem::parse_with(input, Relisting::Warn).unwrap_err();
+            (err.line, err.column, err.message)
+        };
+        assert_eq!(err("$ cd /\n$ rm x"), (2, 6, String::from("x does not exist")));
+        assert_eq!(err("$ mkdir a\n$ mkdir a"), (2, 9, String::from("cannot create directory a")));
+        assert_eq!(err("$ mkdir a\n$ mv a a/b"), (2, 8, String::from("cannot move a to a/b")));
+        assert_eq!(err("$ mkdir a\n$ mv / a"), (2, 6, String::from("cannot move /")));
+        assert_eq!(err("$ cd /\n$ ls\ndir .."), (3, 5, String::from("invalid name \"..\"")));
+        assert_eq!(err("$ cd /\n$ ls\ndir  x"), (3, 5, String::from("invalid name \" x\"")));
+        assert_eq!(err("$ cd /\n$ ls\n10  b"), (3, 4, String::from("invalid name \" b\"")));
+        assert_eq!(err("$ cd /\n10 b"), (2, 1, String::from("output outside of a listing")));
+        assert_eq!(err("$ cd /\n$ cd x\n$ ls"), (3, 1, String::from("/x/ does not exist")));
+    }
+
+    #[test]
+    fn test_change_directory() {
+        let pwd = |input: &str| FileSystem::parse_with(input, Relisting::Reject).unwrap().0.pwd.concat();
+        let input = "$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\ndir b\n$ cd /";
+        assert_eq!(pwd(&format!("{}\n$ cd /a", input)), "/a/");
+        assert_eq!(pwd(&format!("{}\n$ cd a/b", input)), "/a/b/");
+        assert_eq!(pwd(&format!("{}\n$ cd /a/b/\n$ cd ../b/./..", input)), "/a/");
+        // The root is its own parent
+        assert_eq!(pwd("$ cd /\n$ cd .."), "/");
+        assert_eq!(pwd("$ cd ..\n$ cd ..\n$ cd x"), "/x/");
+        let fs = input_generator("$ cd /\n$ ls\ndir a\n$ cd /a\n$ ls\n4 c").unwrap();
+        assert_eq!(fs.file_size("/a/c"), Some(4));
+    }
+
+    #[test]
+    fn test_relisting() {
+        let input = "$ cd /
+$ ls
+dir a
+10 b
+10 b
+$ cd a
+$ ls
+1 c
+$ cd /
+$ ls
+20 b
+dir d";
+        let (fs, warnings) = FileSystem::parse_with(input, Relisting::Warn).unwrap();
+        let warnings = warnings
+            .iter()
+            .map(|w| (w.line, w.column, w.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            [
+                (5, 4, "b is listed twice"),
+                (12, 5, "new directory /d/"),
+                (11, 4, "/b changed size from 10 to 20"),
+                (10, 1, "/a/ is gone"),
+            ]
+        );
+        assert_eq!(sizes(&fs), [(String::from("/"), 20), (String::from("/d/"), 0)]);
+
+        let err = FileSystem::parse_with(input, Relisting::Reject).unwrap_err();
+        assert_eq!((err.line, err.column), (5, 4));
+
+        // Listing again without changes is fine, and does not count twice
+        let again = "$ cd /\n$ ls\ndir a\n10 b\n$ ls\n10 b\ndir a\n$ cd a\n$ ls\n5 c\n$ cd /\n$ ls\ndir a\n10 b";
+        let (fs, warnings) = FileSystem::parse_with(again, Relisting::Reject).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(sizes(&fs), [(String::from("/"), 15), (String::from("/a/"), 5)]);
+    }
+
+    /// A transcript listing every directory once, directory `i > 0` being a
+    /// child of some directory before it.
+    fn transcript() -> impl Strategy<Value = String> {
+        prop::collection::vec((any::<Index>(), prop::collection::vec(1..1000u32, 0..4)), 1..20)
+            .prop_map(|dirs| {
+                let mut children = vec![vec![]; dirs.len()];
+                for (i, (parent, _)) in dirs.iter().enumerate().skip(1) {
+                    children[parent.index(i)].push(i);
+                }
+                fn visit(dir: usize, children: &[Vec<usize>], dirs: &[(Index, Vec<u32>)], out: &mut String) {
+                    out.push_str("$ ls\n");
+                    for child in children[dir].iter() {
+                        out.push_str(&format!("dir d{}\n", child));
+                    }
+                    for (j, size) in dirs[dir].1.iter().enumerate() {
+                        out.push_str(&format!("{} f{}\n", size, j));
+                    }
+                    for child in children[dir].iter() {
+                        out.push_str(&format!("$ cd d{}\n", child));
+                        visit(*child, children, dirs, out);
+                        out.push_str("$ cd ..\n");
+                    }
+                }
+                let mut out = String::from("$ cd /\n");
+                visit(0, &children, &dirs, &mut out);
+                out
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_directory_sizes_matches_recursion(input in transcript()) {
+            let fs = input_generator(&input).unwrap();
+            let sizes = fs.directory_sizes();
+            prop_assert_eq!(sizes.len(), fs.tree.len());
+            for (path, size) in sizes {
+                prop_assert_eq!(size, fs.get_directory_size(&path));
+            }
+        }
     }
 }
@@ -0,0 +1,261 @@
+use std::fmt::Write as _;
+
+use super::FileSystem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Added { size: u32 },
+    Removed { size: u32 },
+    Resized { from: u32, to: u32 },
+}
+
+/// A file or directory that differs between two snapshots. Directories end
+/// with `/` and are reported with their total size; the contents of an added
+/// or removed directory are not reported separately.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryChange {
+    pub path: String,
+    pub change: Change,
+}
+
+/// The total size of a directory found in both snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeDelta {
+    pub path: String,
+    pub before: u32,
+    pub after: u32,
+}
+
+impl SizeDelta {
+    pub fn delta(&self) -> i64 {
+        self.after as i64 - self.before as i64
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diff {
+    /// Sorted by path.
+    pub changes: Vec<EntryChange>,
+    /// Directories whose total size changed, sorted by path.
+    pub sizes: Vec<SizeDelta>,
+}
+
+fn parent(path: &str) -> &str {
+    FileSystem::split_path(path).map_or("", |(parent, _)| parent)
+}
+
+/// What changed from `before` to `after`.
+pub fn diff(before: &FileSystem, after: &FileSystem) -> Diff {
+    let before_sizes = before.directory_sizes();
+    let after_sizes = after.directory_sizes();
+    let mut changes = vec![];
+    let mut sizes = vec![];
+
+    for (path, dir) in before.tree.iter() {
+        let Some(other) = after.tree.get(path) else {
+            if after.is_dir(parent(path)) {
+                let size = before_sizes[path];
+                changes.push(EntryChange {
+                    path: path.clone(),
+                    change: Change::Removed { size },
+                });
+            }
+            continue;
+        };
+        if before_sizes[path] != after_sizes[path] {
+            sizes.push(SizeDelta {
+                path: path.clone(),
+                before: before_sizes[path],
+                after: after_sizes[path],
+            });
+        }
+        for file in dir.files.iter() {
+            let change = match other.files.iter().find(|f| f.name == file.name) {
+                None => Change::Removed { size: file.size },
+                Some(f) if f.size != file.size => Change::Resized {
+                    from: file.size,
+                    to: f.size,
+                },
+                Some(_) => continue,
+            };
+            changes.push(EntryChange {
+                path: format!("{}{}", path, file.name),
+                change,
+            });
+        }
+        let added = other
+            .files
+            .iter()
+            .filter(|f| !dir.files.iter().any(|file| file.name == f.name))
+            .map(|f| EntryChange {
+                path: format!("{}{}", path, f.name),
+                change: Change::Added { size: f.size },
+            });
+        changes.extend(added);
+    }
+    for path in after.tree.keys() {
+        if !before.is_dir(path) && before.is_dir(parent(path)) {
+            changes.push(EntryChange {
+                path: path.clone(),
+                change: Change::Added {
+                    size: after_sizes[path],
+                },
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    sizes.sort_by(|a, b| a.path.cmp(&b.path));
+    Diff { changes, sizes }
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.sizes.is_empty()
+    }
+
+    /// A report in the style of `diff -u`, with one hunk per directory whose
+    /// size or direct contents changed. Empty if nothing changed.
+    pub fn report(&self, before: &str, after: &str) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let mut dirs = self
+            .sizes
+            .iter()
+            .map(|s| s.path.as_str())
+            .chain(self.changes.iter().map(|c| parent(&c.path)))
+            .collect::<Vec<_>>();
+        dirs.sort();
+        dirs.dedup();
+
+        let mut ret = format!("--- {}\n+++ {}\n", before, after);
+        for dir in dirs {
+            match self.sizes.iter().find(|s| s.path == dir) {
+                Some(s) => writeln!(ret, "@@ {} {} -> {} ({:+}) @@", dir, s.before, s.after, s.delta()),
+                None => writeln!(ret, "@@ {} @@", dir),
+            }
+            .unwrap();
+            for c in self.changes.iter().filter(|c| parent(&c.path) == dir) {
+                match c.change {
+                    Change::Added { size } => writeln!(ret, "+{} {}", c.path, size),
+                    Change::Removed { size } => writeln!(ret, "-{} {}", c.path, size),
+                    Change::Resized { from, to } => writeln!(ret, "-{} {}\n+{} {}", c.path, from, c.path, to),
+                }
+                .unwrap();
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day7::Day7, solution::Solution};
+
+    const BEFORE: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    fn diff_of(before: &str, after: &str) -> Diff {
+        diff(&Day7::parse(before).unwrap(), &Day7::parse(after).unwrap())
+    }
+
+    #[test]
+    fn test_identical() {
+        let diff = diff_of(BEFORE, BEFORE);
+        assert!(diff.is_empty());
+        assert_eq!(diff.report("before", "after"), "");
+    }
+
+    #[test]
+    fn test_changes() {
+        let after = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+dir d
+dir x
+$ cd a
+$ ls
+29116 f
+2557 g
+62596 h.lst
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214300 k
+100 l
+$ cd ..
+$ cd x
+$ ls
+dir y
+$ cd y
+$ ls
+7 z";
+        let diff = diff_of(BEFORE, after);
+        let change = |path: &str, change| EntryChange {
+            path: String::from(path),
+            change,
+        };
+        assert_eq!(
+            diff.changes,
+            [
+                change("/a/e/", Change::Removed { size: 584 }),
+                change("/c.dat", Change::Removed { size: 8504156 }),
+                change("/d/k", Change::Resized { from: 7214296, to: 7214300 }),
+                change("/d/l", Change::Added { size: 100 }),
+                change("/x/", Change::Added { size: 7 }),
+            ]
+        );
+        let deltas = diff.sizes.iter().map(|s| (s.path.as_str(), s.delta())).collect::<Vec<_>>();
+        assert_eq!(deltas, [("/", -8504156 - 584 + 4 + 100 + 7), ("/a/", -584), ("/d/", 104)]);
+        assert_eq!(
+            diff.report("monday", "friday"),
+            "--- monday
++++ friday
+@@ / 48381165 -> 39876536 (-8504629) @@
+-/c.dat 8504156
++/x/ 7
+@@ /a/ 94853 -> 94269 (-584) @@
+-/a/e/ 584
+@@ /d/ 24933642 -> 24933746 (+104) @@
+-/d/k 7214296
++/d/k 7214300
++/d/l 100
+"
+        );
+    }
+
+    #[test]
+    fn test_same_size_swap() {
+        // Nothing changes size, but the hunk still shows the renamed file
+        let diff = diff_of("$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\n5 b", "$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\n5 c");
+        assert!(diff.sizes.is_empty());
+        assert_eq!(diff.report("1", "2"), "--- 1\n+++ 2\n@@ /a/ @@\n-/a/b 5\n+/a/c 5\n");
+    }
+}
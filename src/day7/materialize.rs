@@ -0,0 +1,190 @@
+//! Converts between a [`FileSystem`] and a real directory tree, so that tools
+//! such as `du` can check the sizes.
+
+use std::{
+    fs::{self, File},
+    io,
+    path::Path,
+};
+
+use super::{is_valid_name, FileSystem};
+
+/// Recreates `fs` under the existing directory `root`. Files are extended to
+/// their size without being written, which leaves them sparse on file
+/// systems that support it. Names that could lead outside `root` are
+/// rejected before anything is written.
+pub fn write_to(fs: &FileSystem, root: &Path) -> io::Result<()> {
+    let mut paths = fs.tree.keys().collect::<Vec<_>>();
+    // Parents before their children
+    paths.sort();
+    for path in paths.iter() {
+        let dirs = path[1..].strip_suffix('/').map_or(vec![], |p| p.split('/').collect());
+        let files = fs.tree[*path].files.iter().map(|f| f.name.as_str());
+        if let Some(name) = dirs.into_iter().chain(files).find(|name| !is_valid_name(name)) {
+            let message = format!("invalid name {:?} in {}", name, path);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+    }
+    for path in paths {
+        let dir = root.join(&path[1..]);
+        fs::create_dir_all(&dir)?;
+        for file in fs.tree[path].files.iter() {
+            File::create(dir.join(&file.name))?.set_len(file.size as u64)?;
+        }
+    }
+    Ok(())
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Builds a [`FileSystem`] from the directory tree under `root`, using the
+/// apparent size of each file. Symbolic links are not followed.
+pub fn read_from(root: &Path) -> io::Result<FileSystem> {
+    let mut ret = FileSystem::new();
+    let mut stack = vec![(root.to_path_buf(), String::from("/"))];
+    while let Some((dir, path)) = stack.pop() {
+        let mut entries = fs::read_dir(&dir)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let name = entry
+                .file_name()
+                .into_string()
+                .map_err(|name| invalid(format!("{:?} is not valid UTF-8", name)))?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                let child = format!("{}{}/", path, name);
+                ret.create_dir(&child);
+                stack.push((entry.path(), child));
+            } else if metadata.is_file() {
+                let size = metadata
+                    .len()
+                    .try_into()
+                    .map_err(|_| invalid(format!("{} is too large", entry.path().display())))?;
+                ret.create_file(&format!("{}{}", path, name), size);
+            }
+        }
+        ret.tree.get_mut(&path).unwrap().listed = true;
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        day7::{diff::diff, Day7},
+        solution::Solution,
+    };
+    use std::{env, path::PathBuf, process::Command};
+
+    const EXAMPLE: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    /// An empty directory for `test`, removed again when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(test: &str) -> Self {
+            let path = env::temp_dir().join(format!("aoc2022-day7-{}-{}", test, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let fs = Day7::parse(EXAMPLE).unwrap();
+        let dir = TempDir::new("round-trip");
+        write_to(&fs, &dir.0).unwrap();
+        assert_eq!(fs::metadata(dir.0.join("d/d.log")).unwrap().len(), 8033020);
+        let read = read_from(&dir.0).unwrap();
+        assert!(diff(&fs, &read).is_empty());
+        assert_eq!(Day7::part1(&read), 95437);
+    }
+
+    #[test]
+    fn test_rejects_escaping_names() {
+        let dir = TempDir::new("escaping");
+        let mut fs = FileSystem::new();
+        assert!(fs.create_file("/..", 10));
+        let err = write_to(&fs, &dir.0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(fs::read_dir(&dir.0).unwrap().count(), 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_matches_du() {
+        let fs = Day7::parse(EXAMPLE).unwrap();
+        let dir = TempDir::new("du");
+        write_to(&fs, &dir.0).unwrap();
+        let output = Command::new("du")
+            .arg("--apparent-size")
+            .arg("--block-size=1")
+            .arg(&dir.0)
+            .output()
+            .expect("du is needed to run this test");
+        assert!(output.status.success());
+        let output = String::from_utf8(output.stdout).unwrap();
+        let mut checked = 0;
+        for line in output.lines() {
+            let (size, path) = line.split_once('\t').unwrap();
+            let path = Path::new(path);
+            // du also counts the directories themselves
+            let dirs = std::iter::once(path.to_path_buf())
+                .chain(read_dirs(path))
+                .map(|d| fs::metadata(d).unwrap().len())
+                .sum::<u64>();
+            let relative = path.strip_prefix(&dir.0).unwrap().to_str().unwrap();
+            let key = match relative {
+                "" => String::from("/"),
+                r => format!("/{}/", r),
+            };
+            assert_eq!(size.parse::<u64>().unwrap() - dirs, fs.get_directory_size(&key) as u64, "{}", key);
+            checked += 1;
+        }
+        assert_eq!(checked, 4);
+    }
+
+    fn read_dirs(path: &Path) -> Vec<PathBuf> {
+        let mut ret = vec![];
+        for entry in fs::read_dir(path).unwrap() {
+            let entry = entry.unwrap();
+            if entry.file_type().unwrap().is_dir() {
+                ret.push(entry.path());
+                ret.extend(read_dirs(&entry.path()));
+            }
+        }
+        ret
+    }
+}
@@ -0,0 +1,305 @@
+use std::io::{self, BufRead, Write};
+
+use super::{resolve, FileSystem, DISK_SIZE, UPDATE_SIZE};
+
+const HELP: &str = "commands:
+  cd <path>        change directory, `..` and absolute paths allowed
+  ls [path]        list a directory the way the transcript does
+  pwd              print the current directory
+  du [path]        size of every directory under path, itself last
+  df               disk usage and space still needed for the update
+  find <pattern>   paths whose name matches, `*` and `?` are wildcards
+  rm <path>        remove a file, or a directory and its contents
+  help, exit";
+
+/// Whether `name` matches `pattern`, where `*` stands for any run of
+/// characters and `?` for a single one.
+fn glob(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| glob(rest, &name[i..])),
+        Some((&p, rest)) => match name.split_first() {
+            Some((&c, name)) => (p == '?' || p == c) && glob(rest, name),
+            None => false,
+        },
+    }
+}
+
+/// A shell over a reconstructed [`FileSystem`], where removals change the
+/// sizes reported afterwards.
+pub struct Shell {
+    fs: FileSystem,
+    /// The current directory, such as `/a/e/`.
+    cwd: String,
+}
+
+impl Shell {
+    pub fn new(fs: FileSystem) -> Self {
+        Self {
+            fs,
+            cwd: String::from("/"),
+        }
+    }
+
+    pub fn file_system(&self) -> &FileSystem {
+        &self.fs
+    }
+
+    fn pwd(&self) -> String {
+        self.cwd.clone()
+    }
+
+    fn dir_arg(&self, arg: Option<&str>) -> Result<String, String> {
+        let path = resolve(&self.cwd, arg.unwrap_or("."));
+        match self.fs.is_dir(&path) {
+            true => Ok(path),
+            false => Err(format!("{}: no such directory", path)),
+        }
+    }
+
+    fn ls(&self, arg: Option<&str>) -> Result<String, String> {
+        let path = self.dir_arg(arg)?;
+        let (dirs, files) = self.fs.list(&path).unwrap();
+        let mut entries = dirs
+            .into_iter()
+            .map(|d| (d, format!("dir {}", d)))
+            .chain(files.into_iter().map(|(f, size)| (f, format!("{} {}", size, f))))
+            .collect::<Vec<_>>();
+        entries.sort();
+        Ok(entries.into_iter().map(|(_, e)| e).collect::<Vec<_>>().join("\n"))
+    }
+
+    fn du(&self, arg: Option<&str>) -> Result<String, String> {
+        let path = self.dir_arg(arg)?;
+        let mut sizes = self
+            .fs
+            .directory_sizes()
+            .into_iter()
+            .filter(|(p, _)| p.starts_with(&path))
+            .collect::<Vec<_>>();
+        // Deepest first, so that each directory comes after its contents
+        sizes.sort_by(|(a, _), (b, _)| b.matches('/').count().cmp(&a.matches('/').count()).then(a.cmp(b)));
+        Ok(sizes
+            .into_iter()
+            .map(|(p, size)| format!("{}\t{}", size, p))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn df(&self) -> String {
+        let used = self.fs.directory_sizes()["/"];
+        let free = DISK_SIZE.saturating_sub(used);
+        format!(
+            "size {}\nused {}\nfree {}\nneeded {}",
+            DISK_SIZE,
+            used,
+            free,
+            UPDATE_SIZE.saturating_sub(free)
+        )
+    }
+
+    fn find(&self, pattern: &str) -> String {
+        let pattern = pattern.chars().collect::<Vec<_>>();
+        let matches = |name: &str| glob(&pattern, &name.chars().collect::<Vec<_>>());
+        let mut found = vec![];
+        let mut stack = vec![String::from("/")];
+        while let Some(dir) = stack.pop() {
+            let (dirs, files) = self.fs.list(&dir).unwrap();
+            for d in dirs {
+                let path = format!("{}{}/", dir, d);
+                if matches(d) {
+                    found.push(path.clone());
+                }
+                stack.push(path);
+            }
+            found.extend(files.into_iter().filter(|(f, _)| matches(f)).map(|(f, _)| format!("{}{}", dir, f)));
+        }
+        found.sort();
+        found.join("\n")
+    }
+
+    fn rm(&mut self, path: &str) -> Result<String, String> {
+        let Some(target) = self.fs.find_from(&self.cwd, path) else {
+            let missing = resolve(&self.cwd, path);
+            return Err(format!("{}: no such file or directory", missing.trim_end_matches('/')));
+        };
+        if target == "/" {
+            return Err(String::from("cannot remove /"));
+        }
+        let freed = match self.fs.file_size(&target) {
+            Some(size) => size,
+            None => self.fs.get_directory_size(&target),
+        };
+        self.fs.remove(&target);
+        // Leave directories that no longer exist
+        while !self.fs.is_dir(&self.cwd) {
+            self.cwd = resolve(&self.cwd, "..");
+        }
+        Ok(format!("freed {}", freed))
+    }
+
+    /// Runs a single command line, returning what it prints. `Ok(None)` means
+    /// the shell should exit.
+    pub fn execute(&mut self, line: &str) -> Result<Option<String>, String> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let output = match words.as_slice() {
+            [] => String::new(),
+            ["exit" | "quit"] => return Ok(None),
+            ["help"] => HELP.to_string(),
+            ["pwd"] => self.pwd(),
+            ["cd", path] => {
+                self.cwd = self.dir_arg(Some(path))?;
+                String::new()
+            }
+            ["ls"] => self.ls(None)?,
+            ["ls", path] => self.ls(Some(path))?,
+            ["du"] => self.du(None)?,
+            ["du", path] => self.du(Some(path))?,
+            ["df"] => self.df(),
+            ["find", pattern] => self.find(pattern),
+            ["rm", path] => self.rm(path)?,
+            [command, ..] => return Err(format!("{}: unknown command or wrong arguments, try `help`", command)),
+        };
+        Ok(Some(output))
+    }
+
+    /// Reads commands from `input` until it ends or `exit`, writing their
+    /// output and errors to `output`. A prompt is written before each command
+    /// if `prompt` is set.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W, prompt: bool) -> io::Result<()> {
+        let mut lines = input.lines();
+        loop {
+            if prompt {
+                write!(output, "{}$ ", self.pwd())?;
+                output.flush()?;
+            }
+            let Some(line) = lines.next() else {
+                break;
+            };
+            match self.execute(&line?) {
+                Ok(None) => break,
+                Ok(Some(text)) if text.is_empty() => {}
+                Ok(Some(text)) => writeln!(output, "{}", text)?,
+                Err(e) => writeln!(output, "error: {}", e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day7::Day7, solution::Solution};
+
+    const EXAMPLE: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    fn shell() -> Shell {
+        Shell::new(Day7::parse(EXAMPLE).unwrap())
+    }
+
+    fn run(shell: &mut Shell, script: &str) -> String {
+        let mut output = vec![];
+        shell.run(script.as_bytes(), &mut output, false).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_glob() {
+        let glob = |p: &str, n: &str| glob(&p.chars().collect::<Vec<_>>(), &n.chars().collect::<Vec<_>>());
+        assert!(glob("*.txt", "b.txt"));
+        assert!(glob("d.*", "d.log"));
+        assert!(glob("?", "e"));
+        assert!(glob("*", ""));
+        assert!(!glob("?", ""));
+        assert!(!glob("*.txt", "b.dat"));
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut shell = shell();
+        assert_eq!(
+            run(&mut shell, "cd a/e\npwd\ncd ../..\nls\ncd /d\npwd\ncd nope\npwd\nls /a"),
+            "/a/e/
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+/d/
+error: /d/nope/: no such directory
+/d/
+dir e
+29116 f
+2557 g
+62596 h.lst
+"
+        );
+    }
+
+    #[test]
+    fn test_du_and_find() {
+        let mut shell = shell();
+        assert_eq!(run(&mut shell, "du /a"), "584\t/a/e/\n94853\t/a/\n");
+        assert_eq!(run(&mut shell, "find d*\nfind ?"), "/d/\n/d/d.ext\n/d/d.log\n/a/\n/a/e/\n/a/e/i\n/a/f\n/a/g\n/d/\n/d/j\n/d/k\n");
+    }
+
+    #[test]
+    fn test_rm_updates_sizes() {
+        let mut shell = shell();
+        assert_eq!(
+            run(&mut shell, "df\ncd a/e\nrm /d\nrm ../f\npwd\nrm .\npwd\ndu /\ndf\nrm /\nrm /x"),
+            "size 70000000
+used 48381165
+free 21618835
+needed 8381165
+freed 24933642
+freed 29116
+/a/e/
+freed 584
+/a/
+65153\t/a/
+23417823\t/
+size 70000000
+used 23417823
+free 46582177
+needed 0
+error: cannot remove /
+error: /x: no such file or directory
+"
+        );
+        let sizes = shell.file_system().directory_sizes();
+        assert_eq!(Day7::part1(shell.file_system()), 65153);
+        assert_eq!(sizes.len(), 2);
+    }
+
+    #[test]
+    fn test_exit_and_errors() {
+        let mut shell = shell();
+        assert_eq!(run(&mut shell, "frobnicate\n\nexit\npwd"), "error: frobnicate: unknown command or wrong arguments, try `help`\n");
+        let mut output = vec![];
+        shell.run("cd a".as_bytes(), &mut output, true).unwrap();
+        assert_eq!(output, b"/$ /a/$ ");
+    }
+}
@@ -1,5 +1,492 @@
-struct Forest {
+use aoc_runner_derive::{aoc, aoc_generator};
+
+use crate::aoc_common::{self, ParseError};
+use crate::error::{self, PuzzleError};
+use crate::solution::Solution;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Left,
+        Direction::Right,
+        Direction::Up,
+        Direction::Down,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The cells of the Bresenham line from `from` to `to`, both included.
+pub fn bresenham(from: (u32, u32), to: (u32, u32)) -> Vec<(u32, u32)> {
+    let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+    let (x1, y1) = (to.0 as i64, to.1 as i64);
+    let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+    let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+    let mut error = dx + dy;
+    let mut ret = vec![(x as u32, y as u32)];
+    while (x, y) != (x1, y1) {
+        let e2 = 2 * error;
+        if e2 >= dy {
+            error += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            error += dx;
+            y += sy;
+        }
+        ret.push((x as u32, y as u32));
+    }
+    ret
+}
+
+/// Per-tree results of the sweeps, stored row by row like the forest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewingDistances {
+    width: u32,
+    height: u32,
+    /// Trees seen from each tree before the view is blocked or the edge is
+    /// reached, per direction.
+    distances: [Vec<u32>; 4],
+    /// Whether each tree is taller than every tree between it and the edge,
+    /// per direction.
+    visible: [Vec<bool>; 4],
+}
+
+impl ViewingDistances {
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    pub fn distance(&self, x: u32, y: u32, direction: Direction) -> u32 {
+        self.distances[direction.index()][self.index(x, y)]
+    }
+
+    /// The viewing distance of every tree towards `direction`, row by row.
+    pub fn distance_map(&self, direction: Direction) -> &[u32] {
+        &self.distances[direction.index()]
+    }
+
+    pub fn visible_from(&self, x: u32, y: u32, direction: Direction) -> bool {
+        self.visible[direction.index()][self.index(x, y)]
+    }
+
+    /// Whether every tree can be seen from the `direction` edge, row by row.
+    pub fn visibility_map(&self, direction: Direction) -> &[bool] {
+        &self.visible[direction.index()]
+    }
+
+    pub fn is_visible(&self, x: u32, y: u32) -> bool {
+        Direction::ALL
+            .iter()
+            .any(|&d| self.visible_from(x, y, d))
+    }
+
+    pub fn scenic_score(&self, x: u32, y: u32) -> u64 {
+        Direction::ALL
+            .iter()
+            .map(|&d| self.distance(x, y, d) as u64)
+            .product()
+    }
+
+    pub fn visible_count(&self) -> usize {
+        (0..(self.width * self.height) as usize)
+            .filter(|&i| self.visible.iter().any(|v| v[i]))
+            .count()
+    }
+
+    pub fn best_scenic_score(&self) -> u64 {
+        (0..(self.width * self.height) as usize)
+            .map(|i| self.distances.iter().map(|d| d[i] as u64).product())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ForestData"))]
+pub struct Forest {
+    trees: Vec<u32>,
+    width: u32,
+    height: u32,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ForestData {
     trees: Vec<u32>,
     width: u32,
     height: u32,
 }
+
+#[cfg(feature = "serde")]
+impl TryFrom<ForestData> for Forest {
+    type Error = String;
+
+    fn try_from(data: ForestData) -> Result<Self, String> {
+        let expected = data.width as u64 * data.height as u64;
+        if data.trees.len() as u64 != expected {
+            return Err(format!("expected {} trees, found {}", expected, data.trees.len()));
+        }
+        Ok(Self {
+            trees: data.trees,
+            width: data.width,
+            height: data.height,
+        })
+    }
+}
+
+impl Forest {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut trees = vec![];
+        let mut width = None;
+        let mut height = 0;
+        for line in aoc_common::lines(input).filter(|l| !l.is_blank()) {
+            let row = line.trimmed();
+            if let Some(i) = row.text.find(|c: char| !c.is_ascii_digit()) {
+                return Err(line.error(row.column + i, "expected a tree height"));
+            }
+            match width {
+                None => width = Some(row.text.len() as u32),
+                Some(w) if w as usize != row.text.len() => {
+                    return Err(line.error(
+                        row.column,
+                        format!("expected {} trees, found {}", w, row.text.len()),
+                    ))
+                }
+                _ => {}
+            }
+            trees.extend(row.text.bytes().map(|b| (b - b'0') as u32));
+            height += 1;
+        }
+        Ok(Self {
+            trees,
+            width: width.unwrap_or(0),
+            height,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn tree(&self, x: u32, y: u32) -> u32 {
+        self.trees[(y * self.width + x) as usize]
+    }
+
+    /// Viewing distances and visibility of every tree in every direction.
+    ///
+    /// Each row and column is swept once per direction with a stack of the
+    /// trees still able to block the view, tallest at the bottom: a tree pops
+    /// every shorter one, and the one left on top, if any, is what stops its
+    /// view. Every tree is pushed and popped at most once per sweep, so this
+    /// is linear in the number of trees.
+    pub fn viewing_distances(&self) -> ViewingDistances {
+        let len = self.trees.len();
+        let mut ret = ViewingDistances {
+            width: self.width,
+            height: self.height,
+            distances: std::array::from_fn(|_| vec![0; len]),
+            visible: std::array::from_fn(|_| vec![false; len]),
+        };
+        let (w, h) = (self.width as usize, self.height as usize);
+        let mut stack = Vec::with_capacity(w.max(h));
+        for direction in Direction::ALL {
+            let distances = &mut ret.distances[direction.index()];
+            let visible = &mut ret.visible[direction.index()];
+            let mut sweep = |line: &mut dyn Iterator<Item = usize>| {
+                stack.clear();
+                for (position, i) in line.enumerate() {
+                    let tree = self.trees[i];
+                    while stack.last().is_some_and(|&(_, t)| t < tree) {
+                        stack.pop();
+                    }
+                    match stack.last() {
+                        Some(&(blocker, _)) => distances[i] = (position - blocker) as u32,
+                        None => {
+                            distances[i] = position as u32;
+                            visible[i] = true;
+                        }
+                    }
+                    stack.push((position, tree));
+                }
+            };
+            // Each line starts at the edge the trees look towards
+            match direction {
+                Direction::Left => (0..h).for_each(|y| sweep(&mut (0..w).map(|x| y * w + x))),
+                Direction::Right => (0..h).for_each(|y| sweep(&mut (0..w).rev().map(|x| y * w + x))),
+                Direction::Up => (0..w).for_each(|x| sweep(&mut (0..h).map(|y| y * w + x))),
+                Direction::Down => (0..w).for_each(|x| sweep(&mut (0..h).rev().map(|y| y * w + x))),
+            }
+        }
+        ret
+    }
+
+    /// Whether nothing stands in the straight line from `from_height` above
+    /// `from` to `to_height` above `to`. Each cell on the Bresenham line
+    /// between them blocks the view when its tree reaches the line's height
+    /// at that point, interpolated linearly along the steps.
+    fn clear_line(&self, from: (u32, u32), from_height: u32, to: (u32, u32), to_height: u32) -> bool {
+        // Always trace from the same end so that the answer is symmetric
+        let (from, from_height, to, to_height) = if from <= to {
+            (from, from_height, to, to_height)
+        } else {
+            (to, to_height, from, from_height)
+        };
+        let cells = bresenham(from, to);
+        let steps = cells.len() as i64 - 1;
+        let (start, end) = (from_height as i64, to_height as i64);
+        // Nothing stands between a cell and itself or a neighbour
+        let between = cells.get(1..cells.len() - 1).unwrap_or(&[]);
+        between
+            .iter()
+            .enumerate()
+            .all(|(i, &(x, y))| {
+                // tree < start + (end - start) * step / steps, without rounding
+                let step = i as i64 + 1;
+                (self.tree(x, y) as i64) * steps < start * steps + (end - start) * step
+            })
+    }
+
+    /// Whether the top of tree `a` can see the top of tree `b`.
+    pub fn can_see(&self, a: (u32, u32), b: (u32, u32)) -> bool {
+        self.clear_line(a, self.tree(a.0, a.1), b, self.tree(b.0, b.1))
+    }
+
+    /// The trees whose top can be seen by an observer at `position` with
+    /// their eyes at `eye_height`, row by row. The observer's own cell is
+    /// left out.
+    pub fn trees_visible_from(&self, position: (u32, u32), eye_height: u32) -> Vec<(u32, u32)> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&cell| cell != position)
+            .filter(|&cell| self.clear_line(position, eye_height, cell, self.tree(cell.0, cell.1)))
+            .collect()
+    }
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+    type Input = Forest;
+    type Output1 = usize;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Forest::parse(input)
+    }
+
+    fn part1(forest: &Self::Input) -> usize {
+        forest.viewing_distances().visible_count()
+    }
+
+    fn part2(forest: &Self::Input) -> u64 {
+        forest.viewing_distances().best_scenic_score()
+    }
+}
+
+#[aoc_generator(day8)]
+pub fn input_generator(input: &str) -> Result<Forest, PuzzleError> {
+    error::parse::<Day8>(input)
+}
+
+#[aoc(day8, part1)]
+pub fn solve_part1(forest: &Forest) -> usize {
+    Day8::part1(forest)
+}
+
+#[aoc(day8, part2)]
+pub fn solve_part2(forest: &Forest) -> u64 {
+    Day8::part2(forest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "30373
+25512
+65332
+33549
+35390";
+
+    /// Trees met walking from `(x, y)` towards `(dx, dy)` until the edge.
+    fn line_of_sight(forest: &Forest, x: u32, y: u32, dx: i64, dy: i64) -> impl Iterator<Item = u32> + '_ {
+        let (mut cx, mut cy) = (x as i64, y as i64);
+        std::iter::from_fn(move || {
+            cx += dx;
+            cy += dy;
+            if cx < 0 || cy < 0 || cx >= forest.width as i64 || cy >= forest.height as i64 {
+                return None;
+            }
+            Some(forest.tree(cx as u32, cy as u32))
+        })
+    }
+
+    /// Whether the tree at `(x, y)` can be seen from outside the grid, by
+    /// walking every ray.
+    fn is_visible(forest: &Forest, x: u32, y: u32) -> bool {
+        let h = forest.tree(x, y);
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .any(|(dx, dy)| line_of_sight(forest, x, y, dx, dy).all(|t| t < h))
+    }
+
+    /// Product of the viewing distances from `(x, y)`, by walking every ray.
+    fn scenic_score(forest: &Forest, x: u32, y: u32) -> u64 {
+        let h = forest.tree(x, y);
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .map(|(dx, dy)| {
+                let mut distance = 0;
+                for t in line_of_sight(forest, x, y, dx, dy) {
+                    distance += 1;
+                    if t >= h {
+                        break;
+                    }
+                }
+                distance
+            })
+            .product()
+    }
+
+    #[test]
+    fn test_visibility() {
+        let forest = Forest::parse(EXAMPLE).unwrap();
+        let visible = (0..5)
+            .flat_map(|y| (0..5).map(move |x| (x, y)))
+            .filter(|&(x, y)| is_visible(&forest, x, y))
+            .count();
+        assert_eq!(visible, 21);
+    }
+
+    #[test]
+    fn test_scenic_score() {
+        let forest = Forest::parse(EXAMPLE).unwrap();
+        assert_eq!(scenic_score(&forest, 2, 1), 4);
+        assert_eq!(scenic_score(&forest, 2, 3), 8);
+        assert_eq!(scenic_score(&forest, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(solve_part1(&input_generator(EXAMPLE).unwrap()), 21);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(solve_part2(&input_generator(EXAMPLE).unwrap()), 8);
+    }
+
+    #[test]
+    fn test_distance_maps() {
+        let distances = Forest::parse(EXAMPLE).unwrap().viewing_distances();
+        assert_eq!(&distances.distance_map(Direction::Up)[5..10], [1, 1, 1, 1, 1]);
+        assert_eq!(&distances.distance_map(Direction::Left)[5..10], [0, 1, 1, 1, 2]);
+        assert_eq!(
+            (0..5).map(|x| distances.visible_from(x, 1, Direction::Right)).collect::<Vec<_>>(),
+            [false, false, true, false, true]
+        );
+        assert_eq!(distances.scenic_score(2, 3), 8);
+        assert!(!distances.is_visible(3, 3));
+    }
+
+    #[test]
+    fn test_bresenham() {
+        assert_eq!(bresenham((0, 0), (3, 1)), [(0, 0), (1, 0), (2, 1), (3, 1)]);
+        assert_eq!(bresenham((2, 2), (2, 0)), [(2, 2), (2, 1), (2, 0)]);
+        assert_eq!(bresenham((0, 2), (2, 0)), [(0, 2), (1, 1), (2, 0)]);
+        assert_eq!(bresenham((1, 1), (1, 1)), [(1, 1)]);
+    }
+
+    #[test]
+    fn test_can_see() {
+        // Sight lines run between tree tops, rising from 1 to 7 here
+        let forest = Forest::parse("1237\n1437").unwrap();
+        assert!(forest.can_see((0, 0), (3, 0)));
+        assert!(forest.can_see((3, 0), (0, 0)));
+        assert!(!forest.can_see((0, 1), (3, 1)));
+        // Neighbours always see each other, equal trees in between block
+        let forest = Forest::parse("5555\n5115").unwrap();
+        assert!(forest.can_see((0, 0), (1, 0)));
+        assert!(!forest.can_see((0, 0), (3, 0)));
+        assert!(forest.can_see((0, 1), (3, 1)));
+        // Diagonals go through the cells Bresenham picks
+        let forest = Forest::parse("300\n090\n003").unwrap();
+        assert!(!forest.can_see((0, 0), (2, 2)));
+        let forest = Forest::parse("300\n010\n003").unwrap();
+        assert!(forest.can_see((0, 0), (2, 2)));
+        assert!(!forest.can_see((2, 0), (0, 2)));
+        // A tree sees itself and its neighbours, diagonal ones included
+        let forest = Forest::parse("12\n34").unwrap();
+        assert!(forest.can_see((0, 0), (0, 0)));
+        assert!(forest.can_see((1, 1), (1, 1)));
+        assert!(forest.can_see((0, 0), (1, 0)));
+        assert!(forest.can_see((1, 1), (0, 1)));
+        assert!(forest.can_see((0, 0), (1, 1)));
+    }
+
+    #[test]
+    fn test_trees_visible_from() {
+        let forest = Forest::parse("000\n050\n000").unwrap();
+        let all_but = |hidden: &[(u32, u32)]| {
+            (0..3)
+                .flat_map(|y| (0..3).map(move |x| (x, y)))
+                .filter(|c| !hidden.contains(c))
+                .collect::<Vec<_>>()
+        };
+        // The lines to (2, 1) and (1, 2) also cross the middle cell
+        let behind = [(0, 0), (2, 1), (1, 2), (2, 2)];
+        assert_eq!(forest.trees_visible_from((0, 0), 1), all_but(&behind));
+        // Half way there, the line is still below the top of 5
+        assert_eq!(forest.trees_visible_from((0, 0), 9), all_but(&behind));
+        assert_eq!(forest.trees_visible_from((0, 0), 11), all_but(&[(0, 0)]));
+        assert_eq!(forest.trees_visible_from((1, 1), 0), all_but(&[(1, 1)]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Forest::parse("303\n2a5").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        let err = Forest::parse("303\n25").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    fn forest() -> impl Strategy<Value = Forest> {
+        (1..12u32, 1..12u32).prop_flat_map(|(width, height)| {
+            prop::collection::vec(0..10u32, (width * height) as usize).prop_map(move |trees| Forest {
+                trees,
+                width,
+                height,
+            })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_sweeps_match_rays(forest in forest()) {
+            let distances = forest.viewing_distances();
+            for y in 0..forest.height() {
+                for x in 0..forest.width() {
+                    prop_assert_eq!(distances.is_visible(x, y), is_visible(&forest, x, y));
+                    prop_assert_eq!(distances.scenic_score(x, y), scenic_score(&forest, x, y));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Errors reported to users, rendered like compiler diagnostics with the
+//! offending input line underlined.
+
+use std::{error::Error, fmt, fmt::Write as _};
+
+use crate::{aoc_common::ParseError, solution::Solution};
+
+/// An error in a day's input, located by line and by the span of byte
+/// columns `column..column + width`. Lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub width: usize,
+    pub message: String,
+    /// The offending input line, if the input has one.
+    pub snippet: Option<String>,
+}
+
+impl PuzzleError {
+    pub fn new(day: u8, error: ParseError, input: &str) -> Self {
+        let snippet = error
+            .line
+            .checked_sub(1)
+            .and_then(|i| input.lines().nth(i))
+            .map(|l| l.strip_suffix('\r').unwrap_or(l).to_string());
+        Self {
+            day,
+            line: error.line,
+            column: error.column,
+            width: error.width,
+            message: error.message,
+            snippet,
+        }
+    }
+
+    /// The error on several lines, with the input line and a caret
+    /// underline:
+    ///
+    /// ```text
+    /// day 4: cannot parse "x": invalid digit found in string
+    ///  --> line 2, column 3
+    ///   |
+    /// 2 | 2-x,4-5
+    ///   |   ^
+    /// ```
+    pub fn render(&self) -> String {
+        let mut ret = format!("day {}: {}\n", self.day, self.message);
+        let gutter = " ".repeat(self.line.to_string().len());
+        write!(ret, "{}--> line {}, column {}", gutter, self.line, self.column).unwrap();
+        let Some(snippet) = &self.snippet else {
+            return ret;
+        };
+        // Columns count bytes, the underline counts characters. Tabs are
+        // kept so that the caret lines up whatever the tab width.
+        let start = self.column.saturating_sub(1).min(snippet.len());
+        let end = (start + self.width).min(snippet.len());
+        let prefix = snippet.get(..start).unwrap_or(snippet);
+        let span = snippet.get(start..end).unwrap_or("");
+        let pad = prefix
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let carets = "^".repeat(span.chars().count().max(1));
+        write!(
+            ret,
+            "\n{} |\n{} | {}\n{} | {}{}",
+            gutter, self.line, snippet, gutter, pad, carets
+        )
+        .unwrap();
+        ret
+    }
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for PuzzleError {}
+
+/// Parses `input` for `S`, keeping enough of it to render errors.
+pub fn parse<S: Solution>(input: &str) -> Result<S::Input, PuzzleError> {
+    S::parse(input).map_err(|e| PuzzleError::new(S::DAY, e, input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day4::Day4, day7::Day7};
+
+    #[test]
+    fn test_render() {
+        let err = parse::<Day4>("2-4,6-8\r\n2-x,4-5\r\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.width), (2, 3, 1));
+        assert_eq!(err.to_string(), "line 2, column 3: cannot parse \"x\": invalid digit found in string");
+        assert_eq!(
+            err.render(),
+            "day 4: cannot parse \"x\": invalid digit found in string
+ --> line 2, column 3
+  |
+2 | 2-x,4-5
+  |   ^"
+        );
+    }
+
+    #[test]
+    fn test_render_span() {
+        let input = "$ cd /\n$ ls\n1 a\n2 b\n3 c\n4 d\n5 e\n6 f\n7 g\n8 h\n9 i\n\t12ab c.txt";
+        let err = parse::<Day7>(input).unwrap_err();
+        assert_eq!(
+            err.render(),
+            "day 7: cannot parse \"12ab\": invalid digit found in string
+  --> line 12, column 2
+   |
+12 | \t12ab c.txt
+   | \t^^^^"
+        );
+    }
+
+    #[test]
+    fn test_render_without_snippet() {
+        let error = ParseError {
+            line: 3,
+            column: 1,
+            width: 1,
+            message: String::from("unexpected end of input"),
+        };
+        let err = PuzzleError::new(5, error, "[A]\n 1");
+        assert_eq!(err.snippet, None);
+        assert_eq!(err.render(), "day 5: unexpected end of input\n --> line 3, column 1");
+    }
+}
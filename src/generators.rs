@@ -0,0 +1,448 @@
+//! Seeded generators of large, valid puzzle inputs for stress-testing the
+//! solvers. Each generator streams its input to a writer and returns the
+//! answers it could work out on its own, without going through the solvers.
+
+use std::io::{self, Write};
+
+/// SplitMix64, small and good enough to make reproducible inputs.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, `n` being positive.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// A number in `lo..=hi`.
+    pub fn between(&mut self, lo: u64, hi: u64) -> u64 {
+        lo + self.below(hi - lo + 1)
+    }
+
+    /// A letter among the first `n` of `alphabet`.
+    fn letter(&mut self, alphabet: &[u8], n: usize) -> u8 {
+        alphabet[self.below(n as u64) as usize]
+    }
+}
+
+/// Answers of a generated input, `None` when the generator cannot tell.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Known {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+/// Day 1: `elves` elves carrying between 1 and `max_snacks` snacks each.
+pub fn day1<W: Write>(out: &mut W, seed: u64, elves: usize, max_snacks: usize) -> io::Result<Known> {
+    let mut rng = Rng::new(seed);
+    let mut top = [0u64; 3];
+    for elf in 0..elves {
+        if elf > 0 {
+            writeln!(out)?;
+        }
+        let mut total = 0;
+        for _ in 0..rng.between(1, max_snacks.max(1) as u64) {
+            let calories = rng.between(1, 70_000);
+            total += calories;
+            writeln!(out, "{}", calories)?;
+        }
+        if total > top[0] {
+            top[0] = total;
+            top.sort_unstable();
+        }
+    }
+    Ok(Known {
+        part1: (elves > 0).then(|| top[2].to_string()),
+        part2: (elves >= 3).then(|| top.iter().sum::<u64>().to_string()),
+    })
+}
+
+const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const CRATES: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+/// The day 7 disk size.
+const DISK: u64 = 70_000_000;
+
+/// Day 5: `stacks` piles of between 1 and `height` crates and `commands`
+/// moves. Moves never take more crates than their pile holds, and both
+/// cranes are replayed here to know the final tops.
+pub fn day5<W: Write>(
+    out: &mut W,
+    seed: u64,
+    stacks: usize,
+    height: usize,
+    commands: usize,
+) -> io::Result<Known> {
+    assert!(stacks >= 2, "day 5 needs at least two stacks");
+    let mut rng = Rng::new(seed);
+    let piles = (0..stacks)
+        .map(|_| {
+            (0..rng.between(1, height.max(1) as u64))
+                .map(|_| rng.letter(CRATES, CRATES.len()))
+                .collect::<Vec<u8>>()
+        })
+        .collect::<Vec<_>>();
+
+    let tallest = piles.iter().map(Vec::len).max().unwrap_or(0);
+    for level in (0..tallest).rev() {
+        let row = piles
+            .iter()
+            .map(|p| match p.get(level) {
+                Some(&c) => format!("[{}]", c as char),
+                None => String::from("   "),
+            })
+            .collect::<Vec<_>>();
+        writeln!(out, "{}", row.join(" "))?;
+    }
+    let labels = (1..=stacks)
+        .map(|i| format!("{:^3}", i))
+        .collect::<Vec<_>>();
+    writeln!(out, "{}", labels.join(" "))?;
+    writeln!(out)?;
+
+    let mut one_by_one = piles.clone();
+    let mut sticky = piles;
+    for _ in 0..commands {
+        let from = loop {
+            let i = rng.below(stacks as u64) as usize;
+            if !one_by_one[i].is_empty() {
+                break i;
+            }
+        };
+        let to = (from + rng.between(1, stacks as u64 - 1) as usize) % stacks;
+        let n = rng.between(1, one_by_one[from].len() as u64) as usize;
+        writeln!(out, "move {} from {} to {}", n, from + 1, to + 1)?;
+
+        for _ in 0..n {
+            let c = one_by_one[from].pop().unwrap();
+            one_by_one[to].push(c);
+        }
+        let at = sticky[from].len() - n;
+        let moved = sticky[from].split_off(at);
+        sticky[to].extend(moved);
+    }
+
+    let tops = |piles: &[Vec<u8>]| {
+        piles
+            .iter()
+            .map(|p| p.last().map_or('.', |&c| c as char))
+            .collect::<String>()
+    };
+    Ok(Known {
+        part1: Some(tops(&one_by_one)),
+        part2: Some(tops(&sticky)),
+    })
+}
+
+/// Day 6: a `length` long signal whose first start-of-packet marker ends after
+/// `packet` characters and first start-of-message marker after `message`.
+///
+/// Each marker is planted as the first letters of the alphabet. Before it,
+/// only the letters of the marker but its last one are used, so no earlier
+/// window can have enough different letters.
+pub fn day6<W: Write>(
+    out: &mut W,
+    seed: u64,
+    length: usize,
+    packet: usize,
+    message: usize,
+) -> io::Result<Known> {
+    assert!(
+        packet >= 4 && message >= packet + 14 && length >= message,
+        "day 6 markers must satisfy 4 <= packet, packet + 14 <= message <= length"
+    );
+    let mut rng = Rng::new(seed);
+    let mut buffer = Vec::with_capacity(1 << 16);
+    let mut flush = |buffer: &mut Vec<u8>, force: bool| -> io::Result<()> {
+        if force || buffer.len() >= 1 << 16 {
+            out.write_all(buffer)?;
+            buffer.clear();
+        }
+        Ok(())
+    };
+
+    let mut filler = |buffer: &mut Vec<u8>, n: usize, letters: usize| -> io::Result<()> {
+        for _ in 0..n {
+            buffer.push(rng.letter(LETTERS, letters));
+            flush(buffer, false)?;
+        }
+        Ok(())
+    };
+
+    filler(&mut buffer, packet - 4, 3)?;
+    buffer.extend_from_slice(&LETTERS[..4]);
+    filler(&mut buffer, message - packet - 14, 13)?;
+    buffer.extend_from_slice(&LETTERS[..14]);
+    filler(&mut buffer, length - message, LETTERS.len())?;
+    writeln!(buffer)?;
+    flush(&mut buffer, true)?;
+
+    Ok(Known {
+        part1: Some(packet.to_string()),
+        part2: Some(message.to_string()),
+    })
+}
+
+/// Marker positions for a `length` long day 6 signal, at least 18: the
+/// packet marker ends halfway through the room left by the message marker,
+/// and the message marker halfway through what follows.
+pub fn day6_markers(length: usize) -> (usize, usize) {
+    assert!(length >= 18, "a day 6 signal needs at least 18 characters");
+    let packet = 4 + (length - 18) / 2;
+    (packet, packet + 14 + (length - packet - 14) / 2)
+}
+
+/// Day 7: a transcript exploring `directories` directories holding up to
+/// `max_files` files each. Every new directory goes under the previous one
+/// half of the time, which makes for very deep trees.
+///
+/// File sizes are scaled from the number of files so that the 70000000 disk
+/// never overflows, which keeps part 2 answerable, and is at least half used
+/// once there are a few hundred files.
+pub fn day7<W: Write>(out: &mut W, seed: u64, directories: usize, max_files: usize) -> io::Result<Known> {
+    let mut rng = Rng::new(seed);
+    // Directory 0 is the root, parents always come before their children
+    let mut parents = vec![0usize];
+    let mut children = vec![vec![]];
+    let mut counts = vec![];
+    for i in 0..directories.max(1) {
+        if i > 0 {
+            let parent = if rng.below(2) == 0 {
+                i - 1
+            } else {
+                rng.below(i as u64) as usize
+            };
+            parents.push(parent);
+            children.push(vec![]);
+            children[parent].push(i);
+        }
+        counts.push(rng.below(max_files as u64 + 1));
+    }
+
+    let count = counts.iter().sum::<u64>();
+    assert!(count <= DISK, "day 7 needs at most one file per byte of disk");
+    let largest = (DISK / count.max(1)).min(300_000);
+    let files = counts
+        .iter()
+        .map(|&n| (0..n).map(|_| rng.between(largest.div_ceil(2), largest)).collect())
+        .collect::<Vec<Vec<u64>>>();
+
+    let mut totals = files.iter().map(|f| f.iter().sum()).collect::<Vec<u64>>();
+    for i in (1..totals.len()).rev() {
+        totals[parents[i]] += totals[i];
+    }
+
+    // Depth-first walk, without recursion so that depth is not an issue
+    writeln!(out, "$ cd /")?;
+    let mut stack = vec![(0usize, 0usize)];
+    while let Some((dir, next)) = stack.pop() {
+        if next == 0 {
+            writeln!(out, "$ ls")?;
+            for child in children[dir].iter() {
+                writeln!(out, "dir d{}", child)?;
+            }
+            for (j, size) in files[dir].iter().enumerate() {
+                writeln!(out, "{} f{}.txt", size, j)?;
+            }
+        }
+        match children[dir].get(next) {
+            Some(&child) => {
+                stack.push((dir, next + 1));
+                writeln!(out, "$ cd d{}", child)?;
+                stack.push((child, 0));
+            }
+            None if dir != 0 => writeln!(out, "$ cd ..")?,
+            None => {}
+        }
+    }
+
+    let needed = totals[0].saturating_sub(DISK - 30_000_000);
+    Ok(Known {
+        part1: Some(totals.iter().filter(|&&t| t < 100_000).sum::<u64>().to_string()),
+        part2: Some(totals.iter().filter(|&&t| t >= needed).min().unwrap().to_string()),
+    })
+}
+
+/// Day 8: a `width` by `height` forest of random tree heights.
+///
+/// Visibility comes from running maxima from each edge, and scenic scores
+/// from walking each ray, which stays short on random forests.
+pub fn day8<W: Write>(out: &mut W, seed: u64, width: usize, height: usize) -> io::Result<Known> {
+    let mut rng = Rng::new(seed);
+    let trees = (0..width * height)
+        .map(|_| rng.below(10) as u8)
+        .collect::<Vec<_>>();
+    for row in trees.chunks(width.max(1)) {
+        let line = row.iter().map(|&t| (b'0' + t) as char).collect::<String>();
+        writeln!(out, "{}", line)?;
+    }
+    Ok(forest_answers(&trees, width, height))
+}
+
+fn forest_answers(trees: &[u8], width: usize, height: usize) -> Known {
+    if width == 0 || height == 0 {
+        return Known::default();
+    }
+
+    let at = |x: usize, y: usize| trees[y * width + x] as i8;
+    let mut visible = vec![false; trees.len()];
+    for y in 0..height {
+        let (mut left, mut right) = (-1, -1);
+        for x in 0..width {
+            visible[y * width + x] |= at(x, y) > left;
+            left = left.max(at(x, y));
+            let x = width - 1 - x;
+            visible[y * width + x] |= at(x, y) > right;
+            right = right.max(at(x, y));
+        }
+    }
+    for x in 0..width {
+        let (mut top, mut bottom) = (-1, -1);
+        for y in 0..height {
+            visible[y * width + x] |= at(x, y) > top;
+            top = top.max(at(x, y));
+            let y = height - 1 - y;
+            visible[y * width + x] |= at(x, y) > bottom;
+            bottom = bottom.max(at(x, y));
+        }
+    }
+
+    let mut best = 0u64;
+    for y in 0..height {
+        for x in 0..width {
+            let mut score = 1u64;
+            for (dx, dy) in [(-1i64, 0i64), (1, 0), (0, -1), (0, 1)] {
+                let (mut cx, mut cy, mut distance) = (x as i64, y as i64, 0);
+                loop {
+                    cx += dx;
+                    cy += dy;
+                    if cx < 0 || cy < 0 || cx >= width as i64 || cy >= height as i64 {
+                        break;
+                    }
+                    distance += 1;
+                    if at(cx as usize, cy as usize) >= at(x, y) {
+                        break;
+                    }
+                }
+                score *= distance;
+            }
+            best = best.max(score);
+        }
+    }
+
+    Known {
+        part1: Some(visible.iter().filter(|&&v| v).count().to_string()),
+        part2: Some(best.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        day7::Day7,
+        solution::{self, Part, Solution},
+    };
+
+    fn check(day: u8, input: &[u8], known: &Known) {
+        let input = std::str::from_utf8(input).unwrap();
+        let solution = solution::get(day).unwrap();
+        if let Some(part1) = &known.part1 {
+            assert_eq!(&solution.run(Part::One, input).unwrap(), part1);
+        }
+        if let Some(part2) = &known.part2 {
+            assert_eq!(&solution.run(Part::Two, input).unwrap(), part2);
+        }
+    }
+
+    #[test]
+    fn test_rng_is_seeded() {
+        let a = (0..5).map(|_| Rng::new(7).next_u64()).collect::<Vec<_>>();
+        assert!(a.windows(2).all(|w| w[0] == w[1]));
+        assert_ne!(Rng::new(7).next_u64(), Rng::new(8).next_u64());
+    }
+
+    #[test]
+    fn test_day1() {
+        let mut input = vec![];
+        let known = day1(&mut input, 1, 1000, 10).unwrap();
+        check(1, &input, &known);
+    }
+
+    #[test]
+    fn test_day5() {
+        for seed in 0..10 {
+            let mut input = vec![];
+            let known = day5(&mut input, seed, 12, 8, 200).unwrap();
+            check(5, &input, &known);
+        }
+    }
+
+    #[test]
+    fn test_day6() {
+        let mut input = vec![];
+        let known = day6(&mut input, 3, 100_000, 5_000, 60_000).unwrap();
+        assert_eq!(input.len(), 100_001);
+        check(6, &input, &known);
+        let mut input = vec![];
+        let known = day6(&mut input, 3, 18, 4, 18).unwrap();
+        assert_eq!(input, b"abcdabcdefghijklmn\n");
+        check(6, &input, &known);
+    }
+
+    #[test]
+    fn test_day6_markers() {
+        for length in (18..200).chain([1_000, 99_999]) {
+            let (packet, message) = day6_markers(length);
+            let mut input = vec![];
+            let known = day6(&mut input, length as u64, length, packet, message).unwrap();
+            check(6, &input, &known);
+        }
+    }
+
+    #[test]
+    fn test_day7() {
+        for (seed, directories) in [(0, 1), (1, 300), (2, 300), (3, 2_000)] {
+            let mut input = vec![];
+            let known = day7(&mut input, seed, directories, 10).unwrap();
+            let used = Day7::parse(std::str::from_utf8(&input).unwrap()).unwrap().get_directory_size("/") as u64;
+            assert!(used <= DISK, "{} directories use {}", directories, used);
+            assert!(known.part2.is_some());
+            check(7, &input, &known);
+        }
+    }
+
+    #[test]
+    fn test_day8() {
+        let mut input = vec![];
+        let known = day8(&mut input, 0, 0, 0).unwrap();
+        assert_eq!(known, Known::default());
+        let mut input = vec![];
+        let known = day8(&mut input, 5, 30, 20).unwrap();
+        assert_eq!(input.len(), 20 * 31);
+        check(8, &input, &known);
+
+        let example = "3037325512653323354935390"
+            .bytes()
+            .map(|b| b - b'0')
+            .collect::<Vec<_>>();
+        assert_eq!(
+            forest_answers(&example, 5, 5),
+            Known {
+                part1: Some(String::from("21")),
+                part2: Some(String::from("8")),
+            }
+        );
+    }
+}
@@ -1,16 +1,29 @@
-#![feature(iter_array_chunks)]
 extern crate aoc_runner;
 
 #[macro_use]
 extern crate aoc_runner_derive;
 
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
+pub mod aoc_common;
+pub mod classify;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod error;
+pub mod generators;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod run_all;
+pub mod solution;
+pub mod trace;
+pub mod verify;
+pub mod visualize;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
+mod xml;
 
 aoc_lib! { year = 2022 }
@@ -1,3 +1,513 @@
-use aoc_runner_derive::aoc_main;
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Read},
+    path::Path,
+    process::ExitCode,
+    thread,
+};
 
-aoc_main! { lib = advent_of_code_2022 }
+use advent_of_code_2022::{
+    classify,
+    day7::{shell::Shell, Day7},
+    error,
+    run_all::{self, Outcome, Status},
+    solution::{self, Part},
+    trace::{self, Filter},
+    verify::{self, Answers},
+};
+
+const USAGE: &str = "usage: advent-of-code-2022 --day <N> [--part <1|2>] [--input <PATH|->] [--format <text|json>] [--from-json]
+       advent-of-code-2022 --day <N> --dump [--input <PATH|->]
+       advent-of-code-2022 --verify [--input <DIR>] [--answers <PATH>]
+       advent-of-code-2022 --all [--input <DIR>] [--answers <PATH>] [--threads <N>] [--junit <PATH>]
+       advent-of-code-2022 --shell [--input <PATH>]
+       advent-of-code-2022 --classify --input <PATH|-> [--format <text|json>]
+
+  -d, --day <N>        day to run
+  -p, --part <1|2>     part to run, both parts when omitted
+  -i, --input <PATH>   input file, `-` for stdin (default: input/2022/day<N>.txt)
+  -f, --format <FMT>   output format, `text` or `json` (default: text)
+      --dump           print the parsed input as JSON instead of solving (serde feature)
+      --from-json      read the input as JSON written by --dump (serde feature)
+      --verify         run every day on its input and check the answers manifest
+      --all            run every day and part concurrently and print a summary table
+      --answers <PATH> answers manifest (default: <DIR>/answers.toml)
+      --threads <N>    worker threads for --all (default: available parallelism)
+      --junit <PATH>   also write the --all results as JUnit XML
+      --shell          explore the day 7 file system with commands read from stdin
+      --classify       guess which day the input belongs to, with a confidence score
+      --trace <SPEC>   print solver events to stderr, e.g. `7=debug` or `all=info`
+                       (default: $AOC_TRACE)
+  -h, --help           print this message";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Mode {
+    Run { day: u8, parts: Vec<Part> },
+    Dump { day: u8 },
+    Verify { answers: Option<String> },
+    All {
+        answers: Option<String>,
+        threads: Option<usize>,
+        junit: Option<String>,
+    },
+    Shell,
+    Classify,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    mode: Mode,
+    input: Option<String>,
+    format: Format,
+    /// The input is a parsed model in JSON rather than puzzle text.
+    from_json: bool,
+    trace: Option<Filter>,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Options>, String> {
+    let mut day = None;
+    let mut parts = vec![Part::One, Part::Two];
+    let mut input = None;
+    let mut format = Format::Text;
+    let mut verify = false;
+    let mut all = false;
+    let mut shell = false;
+    let mut classify = false;
+    let mut dump = false;
+    let mut from_json = false;
+    let mut threads = None;
+    let mut junit = None;
+    let mut answers = None;
+    let mut trace = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", name))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-d" | "--day" => {
+                let v = value("--day")?;
+                day = Some(v.parse().map_err(|_| format!("invalid day {:?}", v))?);
+            }
+            "-p" | "--part" => {
+                let v = value("--part")?;
+                let part = v
+                    .parse()
+                    .ok()
+                    .and_then(Part::from_number)
+                    .ok_or_else(|| format!("invalid part {:?}, expected 1 or 2", v))?;
+                parts = vec![part];
+            }
+            "-i" | "--input" => input = Some(value("--input")?),
+            "-f" | "--format" => {
+                format = match value("--format")?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    v => return Err(format!("invalid format {:?}, expected text or json", v)),
+                }
+            }
+            "--verify" => verify = true,
+            "--all" => all = true,
+            "--shell" => shell = true,
+            "--classify" => classify = true,
+            "--dump" | "--from-json" if !cfg!(feature = "serde") => {
+                return Err(format!("{} requires the serde feature", arg))
+            }
+            "--dump" => dump = true,
+            "--from-json" => from_json = true,
+            "--answers" => answers = Some(value("--answers")?),
+            "--threads" => {
+                let v = value("--threads")?;
+                threads = Some(
+                    v.parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("invalid thread count {:?}", v))?,
+                );
+            }
+            "--junit" => junit = Some(value("--junit")?),
+            "--trace" => trace = Some(Filter::parse(&value("--trace")?)?),
+            _ => return Err(format!("unexpected argument {:?}", arg)),
+        }
+    }
+    let mode = if verify {
+        Mode::Verify { answers }
+    } else if all {
+        Mode::All {
+            answers,
+            threads,
+            junit,
+        }
+    } else if shell {
+        Mode::Shell
+    } else if classify {
+        if input.is_none() {
+            return Err(String::from("--classify needs an --input"));
+        }
+        Mode::Classify
+    } else if dump {
+        Mode::Dump {
+            day: day.ok_or("missing --day")?,
+        }
+    } else {
+        Mode::Run {
+            day: day.ok_or("missing --day")?,
+            parts,
+        }
+    };
+    Ok(Some(Options {
+        mode,
+        input,
+        format,
+        from_json,
+        trace,
+    }))
+}
+
+fn read_input(options: &Options, day: u8) -> io::Result<String> {
+    match options.input.as_deref() {
+        Some(path) => read_path(path),
+        None => fs::read_to_string(format!("input/2022/day{}.txt", day)),
+    }
+}
+
+/// Reads `path`, or stdin for `-`.
+fn read_path(path: &str) -> io::Result<String> {
+    match path {
+        "-" => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+        path => fs::read_to_string(path),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut ret = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+/// The outcomes of `--verify` or `--all` as a JSON array, with the expected
+/// answers from `answers` when there is a manifest.
+fn outcomes_json(outcomes: &[Outcome], answers: Option<&Answers>) -> String {
+    let outcomes = outcomes
+        .iter()
+        .map(|o| {
+            let (status, message) = match &o.status {
+                Status::Ok => ("ok", None),
+                Status::Fail { .. } => ("fail", None),
+                Status::Missing => ("missing", None),
+                Status::Error(e) => ("error", Some(e.clone())),
+                Status::Invalid(e) => ("error", Some(e.to_string())),
+                Status::Panicked(e) => ("panicked", Some(e.clone())),
+            };
+            let opt = |s: Option<&str>| s.map_or(String::from("null"), json_string);
+            format!(
+                r#"{{"day":{},"part":{},"answer":{},"expected":{},"time_ns":{},"status":"{}","message":{}}}"#,
+                o.day,
+                o.part.number(),
+                opt(o.answer.as_deref()),
+                opt(answers.and_then(|a| a.get(o.day, o.part))),
+                o.elapsed.as_nanos(),
+                status,
+                opt(message.as_deref())
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", outcomes.join(","))
+}
+
+fn run(options: &Options, day: u8, parts: &[Part]) -> Result<(), String> {
+    let solution = solution::get(day).ok_or_else(|| format!("day {} is not solved", day))?;
+    let input = read_input(options, day).map_err(|e| format!("cannot read input: {}", e))?;
+    let mut answers = vec![];
+    for &part in parts.iter() {
+        #[cfg(feature = "serde")]
+        let answer = match options.from_json {
+            true => solution.run_json(part, &input),
+            false => solution.run(part, &input),
+        };
+        #[cfg(not(feature = "serde"))]
+        let answer = solution.run(part, &input);
+        let answer = answer.map_err(|e| e.render())?;
+        answers.push((part, answer));
+    }
+    match options.format {
+        Format::Text => {
+            for (part, answer) in answers {
+                println!("Day {} - Part {}: {}", day, part.number(), answer);
+            }
+        }
+        Format::Json => {
+            let answers = answers
+                .iter()
+                .map(|(part, answer)| {
+                    format!(
+                        r#"{{"day":{},"part":{},"answer":{}}}"#,
+                        day,
+                        part.number(),
+                        json_string(answer)
+                    )
+                })
+                .collect::<Vec<_>>();
+            println!("[{}]", answers.join(","));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn run_dump(options: &Options, day: u8) -> Result<(), String> {
+    let solution = solution::get(day).ok_or_else(|| format!("day {} is not solved", day))?;
+    let input = read_input(options, day).map_err(|e| format!("cannot read input: {}", e))?;
+    let json = solution.dump(&input).map_err(|e| e.render())?;
+    println!("{}", json);
+    Ok(())
+}
+
+#[cfg(not(feature = "serde"))]
+fn run_dump(_: &Options, _: u8) -> Result<(), String> {
+    Err(String::from("--dump requires the serde feature"))
+}
+
+fn run_verify(options: &Options, answers: Option<&str>) -> Result<(), String> {
+    let input_dir = Path::new(options.input.as_deref().unwrap_or("input/2022"));
+    let answers = match answers {
+        Some(path) => Answers::load(Path::new(path))?,
+        None => Answers::load(&input_dir.join("answers.toml"))?,
+    };
+    let outcomes = verify::verify(input_dir, &answers);
+    match options.format {
+        Format::Text => {
+            for outcome in outcomes.iter() {
+                println!("{}", outcome);
+            }
+        }
+        Format::Json => println!("{}", outcomes_json(&outcomes, Some(&answers))),
+    }
+    let failed = outcomes.iter().filter(|o| !o.is_success()).count();
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{} of {} checks failed", n, outcomes.len())),
+    }
+}
+
+fn run_all(
+    options: &Options,
+    answers: Option<&str>,
+    threads: Option<usize>,
+    junit: Option<&str>,
+) -> Result<(), String> {
+    let input_dir = Path::new(options.input.as_deref().unwrap_or("input/2022"));
+    // The manifest is optional here, answers are only checked when there is one
+    let answers = match answers {
+        Some(path) => Some(Answers::load(Path::new(path))?),
+        None => {
+            let path = input_dir.join("answers.toml");
+            match path.exists() {
+                true => Some(Answers::load(&path)?),
+                false => None,
+            }
+        }
+    };
+    let threads = threads
+        .or_else(|| thread::available_parallelism().map(usize::from).ok())
+        .unwrap_or(1);
+    let input = |day: u8| {
+        fs::read_to_string(input_dir.join(format!("day{}.txt", day)))
+            .map_err(|e| format!("cannot read input: {}", e))
+    };
+    let outcomes = run_all::run_all(solution::registry(), input, answers.as_ref(), threads);
+
+    match options.format {
+        Format::Text => {
+            println!("{}", run_all::table(&outcomes));
+            for o in outcomes.iter() {
+                if let Status::Invalid(e) = &o.status {
+                    eprintln!("\nerror: {}", e.render());
+                }
+            }
+        }
+        Format::Json => println!("{}", outcomes_json(&outcomes, answers.as_ref())),
+    }
+    if let Some(path) = junit {
+        fs::write(path, run_all::junit(&outcomes))
+            .map_err(|e| format!("cannot write {}: {}", path, e))?;
+    }
+    let failed = outcomes.iter().filter(|o| !o.is_success()).count();
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{} of {} runs failed", n, outcomes.len())),
+    }
+}
+
+fn run_shell(options: &Options) -> Result<(), String> {
+    let input = match options.input.as_deref() {
+        Some("-") => return Err(String::from("--shell reads commands from stdin, the transcript must be a file")),
+        Some(path) => fs::read_to_string(path),
+        None => fs::read_to_string("input/2022/day7.txt"),
+    };
+    let input = input.map_err(|e| format!("cannot read input: {}", e))?;
+    let fs = error::parse::<Day7>(&input).map_err(|e| e.render())?;
+    let stdin = io::stdin();
+    let prompt = stdin.is_terminal();
+    Shell::new(fs)
+        .run(stdin.lock(), io::stdout().lock(), prompt)
+        .map_err(|e| e.to_string())
+}
+
+fn run_classify(options: &Options) -> Result<(), String> {
+    let path = options.input.as_deref().ok_or("--classify needs an --input")?;
+    let input = read_path(path).map_err(|e| format!("cannot read input: {}", e))?;
+    let guesses = classify::classify(&input);
+    if guesses.is_empty() {
+        return Err(String::from("the input does not look like any solved day"));
+    }
+    match options.format {
+        Format::Text => {
+            for guess in guesses {
+                println!("Day {} - confidence {:.2}", guess.day, guess.confidence);
+            }
+        }
+        Format::Json => {
+            let guesses = guesses
+                .iter()
+                .map(|g| format!(r#"{{"day":{},"confidence":{}}}"#, g.day, g.confidence))
+                .collect::<Vec<_>>();
+            println!("[{}]", guesses.join(","));
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if let Some(filter) = options.trace.clone() {
+        trace::set_filter(filter);
+    }
+    let result = match &options.mode {
+        Mode::Run { day, parts } => run(&options, *day, parts),
+        Mode::Dump { day } => run_dump(&options, *day),
+        Mode::Verify { answers } => run_verify(&options, answers.as_deref()),
+        Mode::All {
+            answers,
+            threads,
+            junit,
+        } => run_all(&options, answers.as_deref(), *threads, junit.as_deref()),
+        Mode::Shell => run_shell(&options),
+        Mode::Classify => run_classify(&options),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> impl Iterator<Item = String> + '_ {
+        s.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(args("--day 5 -p 2 -i - --format json")),
+            Ok(Some(Options {
+                mode: Mode::Run {
+                    day: 5,
+                    parts: vec![Part::Two],
+                },
+                input: Some(String::from("-")),
+                format: Format::Json,
+                from_json: false,
+                trace: None,
+            }))
+        );
+        assert_eq!(
+            parse_args(args("-d 1")).unwrap().unwrap().mode,
+            Mode::Run {
+                day: 1,
+                parts: vec![Part::One, Part::Two],
+            }
+        );
+        assert_eq!(
+            parse_args(args("--verify --answers a.toml"))
+                .unwrap()
+                .unwrap()
+                .mode,
+            Mode::Verify {
+                answers: Some(String::from("a.toml")),
+            }
+        );
+        assert_eq!(
+            parse_args(args("-d 7 --trace 7=debug"))
+                .unwrap()
+                .unwrap()
+                .trace,
+            Some(Filter::parse("7=debug").unwrap())
+        );
+        assert_eq!(
+            parse_args(args("--all --threads 2 --junit r.xml")).unwrap().unwrap().mode,
+            Mode::All {
+                answers: None,
+                threads: Some(2),
+                junit: Some(String::from("r.xml")),
+            }
+        );
+        assert!(parse_args(args("--all --threads 0")).is_err());
+        assert_eq!(parse_args(args("--shell -i t.txt")).unwrap().unwrap().mode, Mode::Shell);
+        assert_eq!(parse_args(args("--classify -i -")).unwrap().unwrap().mode, Mode::Classify);
+        assert!(parse_args(args("--classify")).is_err());
+        if cfg!(feature = "serde") {
+            assert_eq!(parse_args(args("--dump -d 4")).unwrap().unwrap().mode, Mode::Dump { day: 4 });
+            assert!(parse_args(args("-d 4 --from-json")).unwrap().unwrap().from_json);
+            assert!(parse_args(args("--dump")).is_err());
+        } else {
+            assert!(parse_args(args("--dump -d 4")).is_err());
+        }
+        assert_eq!(parse_args(args("-d 1 --help")), Ok(None));
+        assert!(parse_args(args("-d 7 --trace 7=loud")).is_err());
+        assert!(parse_args(args("--part 3 -d 1")).is_err());
+        assert!(parse_args(args("-p 1")).is_err());
+        assert!(parse_args(args("-d")).is_err());
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("CMZ"), r#""CMZ""#);
+        assert_eq!(json_string("a\"b\n"), r#""a\"b\u000a""#);
+    }
+}
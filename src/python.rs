@@ -0,0 +1,325 @@
+//! Python bindings, built as the `advent_of_code_2022` extension module with
+//! `maturin develop --features python`:
+//!
+//! ```python
+//! from advent_of_code_2022 import day5, day7
+//!
+//! fs = day7.parse(open("input/2022/day7.txt").read())
+//! fs.directory_sizes()["/"]
+//!
+//! ship = day5.parse(open("input/2022/day5.txt").read())
+//! while ship.step(sticky=True):
+//!     print(ship.top())
+//! ```
+//!
+//! Every `dayN` module has `parse(text)`, `part1(text)` and `part2(text)`.
+//! Parse errors are raised as `ValueError`.
+
+use std::collections::HashMap;
+
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyModule, IntoPyObjectExt};
+
+use crate::{
+    aoc_common::ParseError,
+    day1::Day1,
+    day2::Day2,
+    day3::Day3,
+    day4::{Assignment, Day4},
+    day5::{Day5, Ship},
+    day6::Day6,
+    day7::{Day7, FileSystem},
+    day8::{Day8, Forest},
+    solution::Solution,
+};
+
+impl From<ParseError> for PyErr {
+    fn from(e: ParseError) -> Self {
+        PyValueError::new_err(e.to_string())
+    }
+}
+
+/// A day 5 ship, stepping through its commands one at a time.
+#[pyclass(name = "Ship", module = "advent_of_code_2022.day5")]
+struct PyShip {
+    initial: Ship,
+    current: Ship,
+    commands: Vec<(usize, usize, usize)>,
+    position: usize,
+}
+
+impl From<Ship> for PyShip {
+    fn from(ship: Ship) -> Self {
+        Self {
+            current: ship.clone_ship(),
+            commands: ship.commands(),
+            initial: ship,
+            position: 0,
+        }
+    }
+}
+
+#[pymethods]
+impl PyShip {
+    #[new]
+    fn new(text: &str) -> PyResult<Self> {
+        Ok(Day5::parse(text)?.into())
+    }
+
+    /// The commands as `(from, to, crates)` tuples.
+    #[getter]
+    fn commands(&self) -> Vec<(usize, usize, usize)> {
+        self.commands.clone()
+    }
+
+    /// Number of commands already run.
+    #[getter]
+    fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The piles ordered by number, bottom crate first.
+    fn piles(&self) -> Vec<String> {
+        self.current
+            .piles()
+            .iter()
+            .map(|pile| pile.iter().collect())
+            .collect()
+    }
+
+    fn top(&self) -> String {
+        self.current.get_crates_on_top()
+    }
+
+    /// Runs the next command and returns it, or `None` once all have run.
+    #[pyo3(signature = (sticky = false))]
+    fn step(&mut self, sticky: bool) -> Option<(usize, usize, usize)> {
+        let command = *self.commands.get(self.position)?;
+        self.current.apply(command, sticky);
+        self.position += 1;
+        Some(command)
+    }
+
+    /// Runs the remaining commands and returns the crates on top.
+    #[pyo3(signature = (sticky = false))]
+    fn run(&mut self, sticky: bool) -> String {
+        while self.step(sticky).is_some() {}
+        self.top()
+    }
+
+    /// Goes back to the piles before the first command.
+    fn reset(&mut self) {
+        self.current = self.initial.clone_ship();
+        self.position = 0;
+    }
+
+    fn part1(&self) -> String {
+        Day5::part1(&self.initial)
+    }
+
+    fn part2(&self) -> String {
+        Day5::part2(&self.initial)
+    }
+
+    fn __len__(&self) -> usize {
+        self.commands.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Ship(piles={}, position={}/{})",
+            self.current.piles().len(),
+            self.position,
+            self.commands.len()
+        )
+    }
+}
+
+/// A day 7 file system rebuilt from a terminal transcript.
+#[pyclass(name = "FileSystem", module = "advent_of_code_2022.day7")]
+struct PyFileSystem(FileSystem);
+
+impl From<FileSystem> for PyFileSystem {
+    fn from(fs: FileSystem) -> Self {
+        Self(fs)
+    }
+}
+
+#[pymethods]
+impl PyFileSystem {
+    #[new]
+    fn new(text: &str) -> PyResult<Self> {
+        Ok(Day7::parse(text)?.into())
+    }
+
+    /// The total size of every directory, keyed by path such as `/a/e/`.
+    fn directory_sizes(&self) -> HashMap<String, u32> {
+        self.0.directory_sizes()
+    }
+
+    fn part1(&self) -> u32 {
+        Day7::part1(&self.0)
+    }
+
+    fn part2(&self) -> u32 {
+        Day7::part2(&self.0)
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.directory_sizes().len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("FileSystem(directories={})", self.__len__())
+    }
+}
+
+/// Defines a `dayN` module whose `parse` converts the parsed input with
+/// `$convert` before handing it to Python.
+macro_rules! day_module {
+    ($module:ident, $solution:ty, $convert:expr $(, $class:ty)*) => {
+        mod $module {
+            use super::*;
+
+            #[pyfunction]
+            fn parse<'py>(py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyAny>> {
+                let convert = $convert;
+                convert(<$solution>::parse(text)?).into_bound_py_any(py)
+            }
+
+            #[pyfunction]
+            fn part1<'py>(py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyAny>> {
+                <$solution>::part1(&<$solution>::parse(text)?).into_bound_py_any(py)
+            }
+
+            #[pyfunction]
+            fn part2<'py>(py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyAny>> {
+                <$solution>::part2(&<$solution>::parse(text)?).into_bound_py_any(py)
+            }
+
+            pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+                m.add_function(wrap_pyfunction!(parse, m)?)?;
+                m.add_function(wrap_pyfunction!(part1, m)?)?;
+                m.add_function(wrap_pyfunction!(part2, m)?)?;
+                $(m.add_class::<$class>()?;)*
+                Ok(())
+            }
+        }
+    };
+}
+
+day_module!(day1, Day1, |input| input);
+day_module!(day2, Day2, |input| input);
+day_module!(day3, Day3, |input| input);
+day_module!(day4, Day4, |input: Vec<Assignment>| {
+    input.iter().map(Assignment::sections).collect::<Vec<_>>()
+});
+day_module!(day5, Day5, PyShip::from, PyShip);
+day_module!(day6, Day6, |input| input);
+day_module!(day7, Day7, PyFileSystem::from, PyFileSystem);
+day_module!(day8, Day8, |forest: Forest| {
+    (0..forest.height())
+        .map(|y| (0..forest.width()).map(|x| forest.tree(x, y)).collect::<Vec<_>>())
+        .collect::<Vec<_>>()
+});
+
+type Register = fn(&Bound<'_, PyModule>) -> PyResult<()>;
+
+#[pymodule]
+fn advent_of_code_2022(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let days: [(&str, Register); 8] = [
+        ("day1", day1::register),
+        ("day2", day2::register),
+        ("day3", day3::register),
+        ("day4", day4::register),
+        ("day5", day5::register),
+        ("day6", day6::register),
+        ("day7", day7::register),
+        ("day8", day8::register),
+    ];
+    let py = m.py();
+    let modules = py.import("sys")?.getattr("modules")?;
+    for (name, register) in days {
+        let day = PyModule::new(py, name)?;
+        register(&day)?;
+        m.add_submodule(&day)?;
+        // Lets `import advent_of_code_2022.day7` work as well
+        modules.set_item(format!("advent_of_code_2022.{}", name), &day)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+    use std::ffi::CStr;
+
+    /// Runs `code` against the module, as imported by a Python interpreter.
+    fn run(code: &CStr) {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = pyo3::wrap_pymodule!(advent_of_code_2022)(py);
+            let modules = py.import("sys").unwrap().getattr("modules").unwrap();
+            modules.set_item("advent_of_code_2022", &module).unwrap();
+            let locals = PyDict::new(py);
+            if let Err(e) = py.run(code, None, Some(&locals)) {
+                e.display(py);
+                panic!("python code failed: {}", e);
+            }
+        });
+    }
+
+    #[test]
+    fn test_parse_and_solve() {
+        run(cr#"
+from advent_of_code_2022 import day1, day4, day6, day8
+assert day1.parse("1\n2\n\n3") == [[1, 2], [3]]
+assert day1.part1("1\n2\n\n4") == 4
+assert day4.parse("2-4,6-8") == [([2, 4], [6, 8])]
+assert day6.part2("mjqjpqmgbljsphdztnvjfqwrcgsmlb") == 19
+assert day8.parse("30\n37") == [[3, 0], [3, 7]]
+try:
+    day4.parse("2-4,6-x")
+    assert False
+except ValueError as e:
+    assert str(e).startswith("line 1, column 7:"), str(e)
+"#);
+    }
+
+    #[test]
+    fn test_file_system() {
+        run(cr#"
+import advent_of_code_2022.day7 as day7
+fs = day7.parse("$ cd /\n$ ls\ndir a\n10 b\n$ cd a\n$ ls\n5 c")
+assert fs.directory_sizes() == {"/": 15, "/a/": 5}
+assert (fs.part1(), len(fs)) == (20, 2)
+assert repr(day7.FileSystem("$ cd /")) == "FileSystem(directories=1)"
+"#);
+    }
+
+    #[test]
+    fn test_ship_steps() {
+        run(cr#"
+from advent_of_code_2022 import day5
+text = """    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+"""
+ship = day5.parse(text)
+assert (len(ship), ship.position) == (4, 0)
+assert ship.piles() == ["ZN", "MCD", "P"]
+assert ship.step() == (2, 1, 1)
+assert ship.piles() == ["ZND", "MC", "P"]
+assert ship.run() == "CMZ" == ship.part1()
+assert ship.step() is None
+ship.reset()
+assert ship.run(sticky=True) == "MCD" == day5.part2(text)
+"#);
+    }
+}
@@ -0,0 +1,393 @@
+//! Runs every registered day and part concurrently, isolating panics so that
+//! one broken solver still lets the others report.
+
+use std::{
+    any::Any,
+    fmt::{self, Write as _},
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::PuzzleError,
+    solution::{Day, Part},
+    verify::Answers,
+    xml::escape,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// Answered, and matched the expected answer if there is a manifest.
+    Ok,
+    Fail { expected: String },
+    /// Answered, but the manifest has no answer to check it against.
+    Missing,
+    /// The input could not be read.
+    Error(String),
+    Invalid(PuzzleError),
+    Panicked(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub day: u8,
+    pub part: Part,
+    pub answer: Option<String>,
+    /// Time spent parsing and solving.
+    pub elapsed: Duration,
+    pub status: Status,
+}
+
+impl Outcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self.status, Status::Ok | Status::Missing)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "day {} part {}: ", self.day, self.part.number())?;
+        let answer = self.answer.as_deref().unwrap_or_default();
+        match &self.status {
+            Status::Ok => write!(f, "ok"),
+            Status::Missing => write!(f, "missing answer (got {})", answer),
+            Status::Error(e) => write!(f, "error: {}", e),
+            Status::Invalid(e) => write!(f, "invalid input\n{}", e.render()),
+            Status::Panicked(e) => write!(f, "panicked: {}", e),
+            Status::Fail { expected } => {
+                writeln!(f, "FAIL")?;
+                writeln!(f, "  - expected: {}", expected)?;
+                write!(f, "  + actual:   {}", answer)
+            }
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(s) => *s,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(s) => s.to_string(),
+            Err(_) => String::from("non-string panic payload"),
+        },
+    }
+}
+
+/// Runs one part of one day, catching its panics, and checks the answer
+/// when there is a manifest.
+pub(crate) fn run_one<F>(day: &dyn Day, part: Part, input: &F, answers: Option<&Answers>) -> Outcome
+where
+    F: Fn(u8) -> Result<String, String>,
+{
+    let start = Instant::now();
+    let result = input(day.day()).map_err(Status::Error).and_then(|input| {
+        match panic::catch_unwind(AssertUnwindSafe(|| day.run(part, &input))) {
+            Ok(result) => result.map_err(Status::Invalid),
+            Err(payload) => Err(Status::Panicked(panic_message(payload))),
+        }
+    });
+    let elapsed = start.elapsed();
+    let (answer, status) = match result {
+        Ok(answer) => {
+            let status = match answers.map(|a| a.get(day.day(), part)) {
+                None => Status::Ok,
+                Some(None) => Status::Missing,
+                Some(Some(expected)) if expected == answer => Status::Ok,
+                Some(Some(expected)) => Status::Fail {
+                    expected: expected.to_string(),
+                },
+            };
+            (Some(answer), status)
+        }
+        Err(status) => (None, status),
+    };
+    Outcome {
+        day: day.day(),
+        part,
+        answer,
+        elapsed,
+        status,
+    }
+}
+
+/// Runs both parts of every day in `days` on `threads` worker threads, with
+/// inputs provided by `input`. Outcomes are ordered by day then part.
+pub fn run_all<F>(days: &[&dyn Day], input: F, answers: Option<&Answers>, threads: usize) -> Vec<Outcome>
+where
+    F: Fn(u8) -> Result<String, String> + Sync,
+{
+    let jobs = days
+        .iter()
+        .flat_map(|day| [Part::One, Part::Two].map(|part| (*day, part)))
+        .collect::<Vec<_>>();
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(jobs.len()));
+
+    thread::scope(|s| {
+        for _ in 0..threads.clamp(1, jobs.len().max(1)) {
+            s.spawn(|| {
+                while let Some(&(day, part)) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let outcome = run_one(day, part, &input, answers);
+                    outcomes.lock().unwrap().push(outcome);
+                }
+            });
+        }
+    });
+
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by_key(|o| (o.day, o.part));
+    outcomes
+}
+
+/// A plain text table with one row per outcome and a totals line.
+pub fn table(outcomes: &[Outcome]) -> String {
+    let answer_width = outcomes
+        .iter()
+        .filter_map(|o| o.answer.as_ref().map(String::len))
+        .max()
+        .unwrap_or(0)
+        .max("answer".len());
+    let mut ret = String::new();
+    writeln!(
+        ret,
+        "{:<4} {:<4} {:<aw$} {:>10}  status",
+        "day",
+        "part",
+        "answer",
+        "time",
+        aw = answer_width
+    )
+    .unwrap();
+    for o in outcomes.iter() {
+        let status = match &o.status {
+            Status::Ok => String::from("ok"),
+            Status::Missing => String::from("ok, no expected answer"),
+            Status::Fail { expected } => format!("FAIL, expected {}", expected),
+            Status::Error(e) => format!("ERROR: {}", e),
+            Status::Invalid(e) => format!("ERROR: {}", e),
+            Status::Panicked(e) => format!("PANIC: {}", e),
+        };
+        writeln!(
+            ret,
+            "{:<4} {:<4} {:<aw$} {:>10.2?}  {}",
+            o.day,
+            o.part.number(),
+            o.answer.as_deref().unwrap_or("-"),
+            o.elapsed,
+            status,
+            aw = answer_width
+        )
+        .unwrap();
+    }
+    let passed = outcomes.iter().filter(|o| o.is_success()).count();
+    let total = outcomes.iter().map(|o| o.elapsed).sum::<Duration>();
+    write!(
+        ret,
+        "{} of {} ok, {:.2?} of solver time",
+        passed,
+        outcomes.len(),
+        total
+    )
+    .unwrap();
+    ret
+}
+
+/// A JUnit XML report with one test case per day and part.
+pub fn junit(outcomes: &[Outcome]) -> String {
+    let count = |f: fn(&Status) -> bool| outcomes.iter().filter(|o| f(&o.status)).count();
+    let failures = count(|s| matches!(s, Status::Fail { .. }));
+    let errors = count(|s| matches!(s, Status::Error(_) | Status::Invalid(_) | Status::Panicked(_)));
+    let total = outcomes.iter().map(|o| o.elapsed).sum::<Duration>();
+
+    let mut ret = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        ret,
+        r#"<testsuites><testsuite name="advent-of-code-2022" tests="{}" failures="{}" errors="{}" time="{:.6}">"#,
+        outcomes.len(),
+        failures,
+        errors,
+        total.as_secs_f64()
+    )
+    .unwrap();
+    for o in outcomes.iter() {
+        write!(
+            ret,
+            r#"  <testcase classname="day{}" name="part{}" time="{:.6}">"#,
+            o.day,
+            o.part.number(),
+            o.elapsed.as_secs_f64()
+        )
+        .unwrap();
+        match &o.status {
+            Status::Ok | Status::Missing => {}
+            Status::Fail { expected } => {
+                let actual = o.answer.as_deref().unwrap_or_default();
+                let message = format!("expected {}, got {}", expected, actual);
+                write!(ret, r#"<failure message="{}"/>"#, escape(&message)).unwrap();
+            }
+            Status::Error(e) => write!(ret, r#"<error message="{}"/>"#, escape(e)).unwrap(),
+            Status::Invalid(e) => write!(
+                ret,
+                r#"<error message="{}">{}</error>"#,
+                escape(&e.to_string()),
+                escape(&e.render())
+            )
+            .unwrap(),
+            Status::Panicked(e) => {
+                let message = format!("panicked: {}", e);
+                write!(ret, r#"<error message="{}"/>"#, escape(&message)).unwrap();
+            }
+        }
+        if let Some(answer) = &o.answer {
+            write!(ret, "<system-out>{}</system-out>", escape(answer)).unwrap();
+        }
+        ret.push_str("</testcase>\n");
+    }
+    ret.push_str("</testsuite></testsuites>\n");
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aoc_common::ParseError, solution};
+
+    struct Broken;
+
+    impl Day for Broken {
+        fn day(&self) -> u8 {
+            99
+        }
+
+        fn check(&self, _: &str) -> Result<(), PuzzleError> {
+            Ok(())
+        }
+
+        fn run(&self, part: Part, input: &str) -> Result<String, PuzzleError> {
+            match part {
+                Part::One => panic!("broken on purpose"),
+                Part::Two => {
+                    let error = ParseError {
+                        line: 1,
+                        column: 2,
+                        width: 1,
+                        message: String::from("bad"),
+                    };
+                    Err(PuzzleError::new(99, error, input))
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        fn dump(&self, _: &str) -> Result<String, PuzzleError> {
+            unreachable!()
+        }
+
+        #[cfg(feature = "serde")]
+        fn run_json(&self, _: Part, _: &str) -> Result<String, PuzzleError> {
+            unreachable!()
+        }
+    }
+
+    fn example(day: u8) -> Result<String, String> {
+        match day {
+            6 => Ok(String::from("mjqjpqmgbljsphdztnvjfqwrcgsmlb")),
+            _ => Ok(String::new()),
+        }
+    }
+
+    #[test]
+    fn test_run_all_isolates_panics() {
+        let days: [&dyn Day; 2] = [&Broken, solution::get(6).unwrap()];
+        let answers = Answers::parse("[day6]\npart1 = \"7\"\npart2 = \"20\"").unwrap();
+        let outcomes = run_all(&days, example, Some(&answers), 4);
+        let statuses = outcomes
+            .iter()
+            .map(|o| (o.day, o.part.number(), o.status.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                (6, 1, Status::Ok),
+                (6, 2, Status::Fail { expected: String::from("20") }),
+                (99, 1, Status::Panicked(String::from("broken on purpose"))),
+                (99, 2, Status::Invalid(PuzzleError {
+                    day: 99,
+                    line: 1,
+                    column: 2,
+                    width: 1,
+                    message: String::from("bad"),
+                    snippet: None,
+                })),
+            ]
+        );
+        assert_eq!(outcomes[1].answer.as_deref(), Some("19"));
+
+        let table = table(&outcomes);
+        assert!(table.contains("PANIC: broken on purpose"));
+        assert!(table.contains("\n1 of 4 ok, "));
+
+        let xml = junit(&outcomes);
+        assert!(xml.contains(r#"tests="4" failures="1" errors="2""#));
+        assert!(xml.contains(r#"<failure message="expected 20, got 19"/><system-out>19</system-out>"#));
+        assert!(xml.contains(r#"<error message="panicked: broken on purpose"/>"#));
+        assert!(xml.contains(r#"<error message="line 1, column 2: bad">day 99: bad"#));
+    }
+
+    #[test]
+    fn test_missing_answer() {
+        let days = [solution::get(6).unwrap()];
+        let answers = Answers::parse("[day6]\npart1 = \"7\"").unwrap();
+        let outcomes = run_all(&days, example, Some(&answers), 1);
+        assert_eq!(outcomes[1].status, Status::Missing);
+        assert!(outcomes[1].is_success());
+        assert_eq!(outcomes[1].to_string(), "day 6 part 2: missing answer (got 19)");
+        assert!(table(&outcomes).contains("\n2 of 2 ok, "));
+    }
+
+    #[test]
+    fn test_outcome_display() {
+        let outcome = Outcome {
+            day: 5,
+            part: Part::One,
+            answer: Some(String::from("CMX")),
+            elapsed: Duration::ZERO,
+            status: Status::Fail {
+                expected: String::from("CMZ"),
+            },
+        };
+        assert_eq!(
+            outcome.to_string(),
+            "day 5 part 1: FAIL\n  - expected: CMZ\n  + actual:   CMX"
+        );
+        let error = crate::error::parse::<crate::day4::Day4>("2-x,4-5").unwrap_err();
+        let outcome = Outcome {
+            day: 4,
+            part: Part::Two,
+            answer: None,
+            elapsed: Duration::ZERO,
+            status: Status::Invalid(error),
+        };
+        assert_eq!(
+            outcome.to_string(),
+            "day 4 part 2: invalid input
+day 4: cannot parse \"x\": invalid digit found in string
+ --> line 1, column 3
+  |
+1 | 2-x,4-5
+  |   ^"
+        );
+    }
+
+    #[test]
+    fn test_run_all_missing_input() {
+        let days = [solution::get(1).unwrap()];
+        let outcomes = run_all(&days, |_| Err(String::from("no input")), None, 1);
+        assert!(outcomes.iter().all(|o| o.status == Status::Error(String::from("no input"))));
+    }
+}
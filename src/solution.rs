@@ -0,0 +1,213 @@
+use std::{fmt::Display, marker::PhantomData};
+
+use crate::aoc_common::ParseError;
+use crate::error::{self, PuzzleError};
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8};
+
+/// Bound on parsed inputs, which can also be read and written as JSON when
+/// the `serde` feature is enabled.
+#[cfg(feature = "serde")]
+pub trait Model: serde::Serialize + serde::de::DeserializeOwned {}
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Model for T {}
+#[cfg(not(feature = "serde"))]
+pub trait Model {}
+#[cfg(not(feature = "serde"))]
+impl<T> Model for T {}
+
+/// A day's puzzle, callable without going through the `aoc-runner` macros.
+///
+/// ```
+/// use advent_of_code_2022::{day6::Day6, solution::Solution};
+///
+/// let signal = Day6::parse("mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap();
+/// assert_eq!(Day6::part1(&signal), 7);
+/// ```
+pub trait Solution {
+    const DAY: u8;
+    type Input: Model;
+    type Output1: Display;
+    type Output2: Display;
+
+    /// Parses and checks `input`. The parts can rely on everything checked
+    /// here and do not fail; models read from JSON get the same checks.
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Self::Output1;
+    fn part2(input: &Self::Input) -> Self::Output2;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+
+    pub fn from_number(n: u8) -> Option<Self> {
+        match n {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None,
+        }
+    }
+}
+
+/// Object-safe view of a [`Solution`], so that days with different input and
+/// output types can live in the same registry.
+pub trait Day: Sync {
+    fn day(&self) -> u8;
+    /// Parses `input` without solving it.
+    fn check(&self, input: &str) -> Result<(), PuzzleError>;
+    /// Parses `input` and runs `part` on it, returning the displayed answer.
+    fn run(&self, part: Part, input: &str) -> Result<String, PuzzleError>;
+    /// Parses `input` and writes the parsed model as JSON.
+    #[cfg(feature = "serde")]
+    fn dump(&self, input: &str) -> Result<String, PuzzleError>;
+    /// Like [`Day::run`], on a model written by [`Day::dump`].
+    #[cfg(feature = "serde")]
+    fn run_json(&self, part: Part, json: &str) -> Result<String, PuzzleError>;
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for ParseError {
+    fn from(e: serde_json::Error) -> Self {
+        // Errors found after reading, such as inconsistent models, have no
+        // position
+        ParseError {
+            line: e.line().max(1),
+            column: e.column().max(1),
+            width: 1,
+            message: e.to_string(),
+        }
+    }
+}
+
+struct Registered<S>(PhantomData<fn() -> S>);
+
+impl<S: Solution> Day for Registered<S> {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn check(&self, input: &str) -> Result<(), PuzzleError> {
+        error::parse::<S>(input).map(|_| ())
+    }
+
+    fn run(&self, part: Part, input: &str) -> Result<String, PuzzleError> {
+        Ok(solve::<S>(part, &error::parse::<S>(input)?))
+    }
+
+    #[cfg(feature = "serde")]
+    fn dump(&self, input: &str) -> Result<String, PuzzleError> {
+        let json = serde_json::to_string(&error::parse::<S>(input)?);
+        json.map_err(|e| PuzzleError::new(S::DAY, e.into(), input))
+    }
+
+    #[cfg(feature = "serde")]
+    fn run_json(&self, part: Part, json: &str) -> Result<String, PuzzleError> {
+        let input = serde_json::from_str(json).map_err(|e| PuzzleError::new(S::DAY, e.into(), json))?;
+        Ok(solve::<S>(part, &input))
+    }
+}
+
+fn solve<S: Solution>(part: Part, input: &S::Input) -> String {
+    match part {
+        Part::One => S::part1(input).to_string(),
+        Part::Two => S::part2(input).to_string(),
+    }
+}
+
+static REGISTRY: &[&dyn Day] = &[
+    &Registered::<day1::Day1>(PhantomData),
+    &Registered::<day2::Day2>(PhantomData),
+    &Registered::<day3::Day3>(PhantomData),
+    &Registered::<day4::Day4>(PhantomData),
+    &Registered::<day5::Day5>(PhantomData),
+    &Registered::<day6::Day6>(PhantomData),
+    &Registered::<day7::Day7>(PhantomData),
+    &Registered::<day8::Day8>(PhantomData),
+];
+
+/// Every registered day, in increasing day order.
+pub fn registry() -> &'static [&'static dyn Day] {
+    REGISTRY
+}
+
+/// The registered solution for `day`, if any.
+pub fn get(day: u8) -> Option<&'static dyn Day> {
+    REGISTRY.iter().find(|d| d.day() == day).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let days = registry().iter().map(|d| d.day()).collect::<Vec<_>>();
+        assert_eq!(days, (1..=8).collect::<Vec<_>>());
+        assert!(get(25).is_none());
+        let day2 = get(2).unwrap();
+        assert_eq!(day2.run(Part::One, "A Y\nB X\nC Z").unwrap(), "15");
+        assert_eq!(day2.run(Part::Two, "A Y\nB X\nC Z").unwrap(), "12");
+        assert!(day2.run(Part::One, "A Y\nB W").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_round_trip() {
+        let examples = [
+            (1, "1000\n2000\n\n4000"),
+            (2, "A Y\nB X\nC Z"),
+            (3, "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg"),
+            (4, "2-4,6-8\n2-8,3-7"),
+            (5, "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n\nmove 1 from 2 to 1\nmove 3 from 1 to 3"),
+            (6, "mjqjpqmgbljsphdztnvjfqwrcgsmlb"),
+            (7, "$ cd /\n$ ls\ndir a\n10 b\n$ cd a\n$ ls\n5 c"),
+            (8, "30373\n25512\n65332\n33549\n35390"),
+        ];
+        for (day, input) in examples {
+            let day = get(day).unwrap();
+            let json = day.dump(input).unwrap();
+            for part in [Part::One, Part::Two] {
+                assert_eq!(day.run_json(part, &json).unwrap(), day.run(part, input).unwrap(), "{}", json);
+            }
+        }
+        assert_eq!(get(4).unwrap().dump("2-4,6-8").unwrap(), r#"[{"first_team":[2,4],"second_team":[6,8]}]"#);
+        let err = get(8).unwrap().run_json(Part::One, "{\n\"trees\": [1, 2], \"width\": 2, \"height\": 2}").unwrap_err();
+        assert_eq!((err.line, err.message.as_str()), (1, "expected 4 trees, found 2"));
+        let err = get(7).unwrap().run_json(Part::One, r#"{"tree": {}}"#).unwrap_err();
+        assert_eq!(err.message, "missing root directory");
+        let err = get(1).unwrap().run_json(Part::One, "[[1], [x]]").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 8));
+
+        // Models the text parser would reject, which used to panic the solvers
+        let invalid = [
+            (1, "[[18446744073709551615],[1]]", "total calories overflow"),
+            (1, "[[18446744073709551615,1]]", "total calories overflow"),
+            (2, "[[5,9]]", "round 0: expected [1-3, 4-6], found [5, 9]"),
+            (2, "[[1]]", "round 0: expected [1-3, 4-6], found [1]"),
+            (3, r#"["ab1c"]"#, "bag 0: expected an item letter, found '1'"),
+            (3, r#"["éa"]"#, "bag 0: expected an item letter, found 'é'"),
+            (3, r#"["aa","aa"]"#, "bag 0: expected a group of 3 bags, found 2"),
+            (5, r#"{"piles":{"1":["A"]},"commands":[{"movement":[1,3],"n_crates":1}]}"#, "command 0: there is no pile 3"),
+            (5, r#"{"piles":{"1":["A"],"2":[]},"commands":[{"movement":[1,2],"n_crates":2}]}"#, "command 0: pile 1 only has 1 crates"),
+            (7, r#"{"tree":{"/":{"name":"/","files":[{"name":"../x","size":1}],"subdirs":[],"listed":true}}}"#, "invalid name \"../x\" in /"),
+            (7, r#"{"tree":{"/":{"name":"/","files":[],"subdirs":[],"listed":true},"/../":{"name":"..","files":[],"subdirs":[],"listed":true}}}"#, "/../ is not a valid directory path"),
+            (7, r#"{"tree":{"/":{"name":"/","files":[],"subdirs":[],"listed":true},"//":{"name":"","files":[],"subdirs":[],"listed":true}}}"#, "// is not a valid directory path"),
+        ];
+        for (day, json, message) in invalid {
+            for part in [Part::One, Part::Two] {
+                let err = get(day).unwrap().run_json(part, json).unwrap_err();
+                assert!(err.message.starts_with(message), "{}: {}", json, err.message);
+            }
+        }
+    }
+}
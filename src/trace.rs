@@ -0,0 +1,281 @@
+//! Structured events emitted by the solvers, silent unless enabled.
+//!
+//! Events are enabled per day with a filter such as `5,7=debug` or
+//! `all=info`, read from the `AOC_TRACE` environment variable or set with
+//! [`set_filter`]. A bare day enables every level. Enabled events go to
+//! stderr, or to the current thread's buffer inside [`capture`].
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    env, fmt,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        OnceLock, RwLock,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("invalid level {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub day: u8,
+    pub level: Level,
+    pub name: &'static str,
+    /// Field values, already formatted with `{:?}`.
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl Event {
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[day {}] {} {}", self.day, self.level, self.name)?;
+        for (key, value) in self.fields.iter() {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Most detailed level enabled for each day.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Filter {
+    all: Option<Level>,
+    days: BTreeMap<u8, Level>,
+}
+
+impl Filter {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Filter::default();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let (target, level) = match directive.split_once('=') {
+                Some((target, level)) => (target, level.parse()?),
+                None => (directive, Level::Trace),
+            };
+            if target == "all" {
+                filter.all = Some(level);
+            } else {
+                let day = target
+                    .strip_prefix("day")
+                    .unwrap_or(target)
+                    .parse()
+                    .map_err(|_| format!("invalid day {:?}", target))?;
+                filter.days.insert(day, level);
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn enabled(&self, day: u8, level: Level) -> bool {
+        self.days
+            .get(&day)
+            .or(self.all.as_ref())
+            .is_some_and(|max| level <= *max)
+    }
+
+    /// Whether no event at all is enabled.
+    pub fn is_empty(&self) -> bool {
+        self.all.is_none() && self.days.is_empty()
+    }
+}
+
+thread_local! {
+    static CAPTURE: RefCell<Option<(Filter, Vec<Event>)>> = const { RefCell::new(None) };
+}
+
+/// Captures in progress on any thread.
+static CAPTURES: AtomicUsize = AtomicUsize::new(0);
+
+struct Global {
+    filter: RwLock<Filter>,
+    /// Whether `filter` enables anything, so that disabled events cost an
+    /// atomic load rather than the lock.
+    any: AtomicBool,
+}
+
+fn global() -> &'static Global {
+    static GLOBAL: OnceLock<Global> = OnceLock::new();
+    GLOBAL.get_or_init(|| {
+        let filter = match env::var("AOC_TRACE") {
+            Ok(spec) => Filter::parse(&spec).unwrap_or_else(|e| {
+                eprintln!("ignoring AOC_TRACE: {}", e);
+                Filter::default()
+            }),
+            Err(_) => Filter::default(),
+        };
+        Global {
+            any: AtomicBool::new(!filter.is_empty()),
+            filter: RwLock::new(filter),
+        }
+    })
+}
+
+/// Replaces the process-wide filter, including the one read from `AOC_TRACE`.
+pub fn set_filter(filter: Filter) {
+    let global = global();
+    let mut current = global.filter.write().unwrap();
+    global.any.store(!filter.is_empty(), Ordering::Relaxed);
+    *current = filter;
+}
+
+pub fn enabled(day: u8, level: Level) -> bool {
+    // Tracing is usually off, and this runs in the solvers' inner loops
+    if CAPTURES.load(Ordering::Relaxed) == 0 && !global().any.load(Ordering::Relaxed) {
+        return false;
+    }
+    let captured = CAPTURE.with(|c| c.borrow().as_ref().map(|(f, _)| f.enabled(day, level)));
+    match captured {
+        Some(enabled) => enabled,
+        None => global().filter.read().unwrap().enabled(day, level),
+    }
+}
+
+pub fn emit(event: Event) {
+    let event = CAPTURE.with(|c| match c.borrow_mut().as_mut() {
+        Some((_, events)) => {
+            events.push(event);
+            None
+        }
+        None => Some(event),
+    });
+    if let Some(event) = event {
+        eprintln!("{}", event);
+    }
+}
+
+/// Restores the capture a [`capture`] call replaced, even if `f` panics.
+struct Restore(Option<(Filter, Vec<Event>)>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CAPTURE.with(|c| *c.borrow_mut() = previous);
+        CAPTURES.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Runs `f` with `filter` on the current thread only, returning the events
+/// it emitted instead of printing them.
+pub fn capture<R>(filter: Filter, f: impl FnOnce() -> R) -> (R, Vec<Event>) {
+    CAPTURES.fetch_add(1, Ordering::Relaxed);
+    let restore = Restore(CAPTURE.with(|c| c.borrow_mut().replace((filter, vec![]))));
+    let ret = f();
+    let (_, events) = CAPTURE.with(|c| c.borrow_mut().take()).unwrap();
+    drop(restore);
+    (ret, events)
+}
+
+/// Emits an event for a day if its level is enabled, formatting the fields
+/// only in that case:
+///
+/// ```
+/// # use advent_of_code_2022::{trace::{self, Filter}, trace_event};
+/// let (_, events) = trace::capture(Filter::parse("6").unwrap(), || {
+///     trace_event!(6, Info, "marker_found", length = 4, position = 7);
+/// });
+/// assert_eq!(events[0].to_string(), "[day 6] INFO marker_found length=4 position=7");
+/// ```
+#[macro_export]
+macro_rules! trace_event {
+    ($day:expr, $level:ident, $name:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::trace::enabled($day, $crate::trace::Level::$level) {
+            $crate::trace::emit($crate::trace::Event {
+                day: $day,
+                level: $crate::trace::Level::$level,
+                name: $name,
+                fields: vec![$((stringify!($key), format!("{:?}", $value))),*],
+            });
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_parse() {
+        let filter = Filter::parse("5, day7=debug, all=warn").unwrap();
+        assert!(filter.enabled(5, Level::Trace));
+        assert!(filter.enabled(7, Level::Debug));
+        assert!(!filter.enabled(7, Level::Trace));
+        assert!(filter.enabled(1, Level::Warn));
+        assert!(!filter.enabled(1, Level::Info));
+        assert!(!Filter::default().enabled(1, Level::Error));
+        assert!(Filter::default().is_empty() && Filter::parse(" , ").unwrap().is_empty());
+        assert!(!filter.is_empty());
+        assert!(Filter::parse("7=loud").is_err());
+        assert!(Filter::parse("x=info").is_err());
+    }
+
+    #[test]
+    fn test_capture() {
+        let (_, events) = capture(Filter::parse("1=info").unwrap(), || {
+            trace_event!(1, Info, "kept", value = "a");
+            trace_event!(1, Debug, "too_detailed");
+            trace_event!(2, Error, "other_day");
+        });
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].field("value"), Some("\"a\""));
+        let (_, events) = capture(Filter::default(), || trace_event!(1, Error, "silent"));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_capture_restored_after_panic() {
+        let (_, events) = capture(Filter::parse("1").unwrap(), || {
+            let inner = std::panic::catch_unwind(|| {
+                capture(Filter::default(), || panic!("solver failed"));
+            });
+            assert!(inner.is_err());
+            trace_event!(1, Info, "outer");
+        });
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "outer");
+        assert!(CAPTURE.with(|c| c.borrow().is_none()));
+    }
+}
@@ -0,0 +1,94 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{
+    run_all::{run_one, Outcome},
+    solution::{self, Part},
+};
+
+/// Expected answers, as recorded in `input/2022/answers.toml`:
+///
+/// ```toml
+/// [day1]
+/// part1 = "68802"
+/// part2 = "205370"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers {
+    answers: BTreeMap<(u8, Part), String>,
+}
+
+impl Answers {
+    pub fn parse(manifest: &str) -> Result<Self, String> {
+        let table = manifest
+            .parse::<toml::Value>()
+            .map_err(|e| e.to_string())?;
+        let table = table.as_table().ok_or("expected a table")?;
+        let mut answers = BTreeMap::new();
+        for (key, parts) in table {
+            let day = key
+                .strip_prefix("day")
+                .and_then(|d| d.parse().ok())
+                .ok_or_else(|| format!("invalid section [{}], expected [day<N>]", key))?;
+            let parts = parts
+                .as_table()
+                .ok_or_else(|| format!("[{}] should be a table", key))?;
+            for (name, answer) in parts {
+                let part = name
+                    .strip_prefix("part")
+                    .and_then(|p| p.parse().ok())
+                    .and_then(Part::from_number)
+                    .ok_or_else(|| format!("invalid key {}.{}, expected part1 or part2", key, name))?;
+                let answer = match answer {
+                    toml::Value::String(s) => s.clone(),
+                    toml::Value::Integer(i) => i.to_string(),
+                    _ => return Err(format!("{}.{} should be a string or an integer", key, name)),
+                };
+                answers.insert((day, part), answer);
+            }
+        }
+        Ok(Self { answers })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let manifest = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Self::parse(&manifest).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+}
+
+/// Runs every registered day on `input_dir/day<N>.txt`, one after the
+/// other, and checks the results against `answers`. A panicking solver only
+/// fails its own check.
+pub fn verify(input_dir: &Path, answers: &Answers) -> Vec<Outcome> {
+    let input = |day: u8| {
+        fs::read_to_string(input_dir.join(format!("day{}.txt", day)))
+            .map_err(|e| format!("cannot read input: {}", e))
+    };
+    let mut ret = vec![];
+    for day in solution::registry() {
+        for part in [Part::One, Part::Two] {
+            ret.push(run_one(*day, part, &input, Some(answers)));
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers_parse() {
+        let answers = Answers::parse("[day5]\npart1 = \"CMZ\"\npart2 = 12\n").unwrap();
+        assert_eq!(answers.get(5, Part::One), Some("CMZ"));
+        assert_eq!(answers.get(5, Part::Two), Some("12"));
+        assert_eq!(answers.get(1, Part::One), None);
+        assert!(Answers::parse("[dayX]\npart1 = \"1\"").is_err());
+        assert!(Answers::parse("[day1]\npart3 = \"1\"").is_err());
+        assert!(Answers::parse("[day1\n").is_err());
+    }
+}
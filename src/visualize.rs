@@ -0,0 +1,433 @@
+//! Renders puzzle states to standalone SVG documents, either a single picture
+//! or a sequence of frames to be written as numbered files and played back
+//! with any image viewer or converter.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    day5::Ship,
+    day6,
+    day7::FileSystem,
+    day8::Forest,
+    xml::escape,
+};
+
+const CELL: f64 = 24.0;
+const MARGIN: f64 = 16.0;
+const CAPTION: f64 = 28.0;
+
+struct Svg {
+    width: f64,
+    height: f64,
+    body: String,
+}
+
+impl Svg {
+    fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64, fill: &str, title: Option<&str>) {
+        write!(
+            self.body,
+            r##"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="#333" stroke-width="0.5""##,
+            x, y, w, h, fill
+        )
+        .unwrap();
+        match title {
+            Some(title) => write!(self.body, "><title>{}</title></rect>", escape(title)).unwrap(),
+            None => self.body.push_str("/>"),
+        }
+        self.body.push('\n');
+    }
+
+    /// Draws `content` centred on `x`, with its baseline at `y`.
+    fn text(&mut self, x: f64, y: f64, size: f64, content: &str) {
+        writeln!(
+            self.body,
+            r#"<text x="{}" y="{}" font-size="{}" text-anchor="middle">{}</text>"#,
+            x,
+            y,
+            size,
+            escape(content)
+        )
+        .unwrap();
+    }
+
+    fn finish(self) -> String {
+        format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="monospace">"#,
+                "\n",
+                r#"<rect width="{w}" height="{h}" fill="white"/>"#,
+                "\n{}</svg>\n"
+            ),
+            self.body,
+            w = self.width,
+            h = self.height
+        )
+    }
+}
+
+/// Mixes `color` with white, from white at `t = 0` to `color` at `t = 1`.
+fn shade(color: (u8, u8, u8), t: f64) -> String {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |c: u8| (255.0 - (255.0 - c as f64) * t).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", mix(color.0), mix(color.1), mix(color.2))
+}
+
+fn render_piles(piles: &[Vec<char>], rows: usize, caption: &str) -> String {
+    let mut svg = Svg::new(
+        2.0 * MARGIN + (piles.len().max(1) as f64) * CELL,
+        2.0 * MARGIN + CAPTION + (rows as f64 + 1.0) * CELL,
+    );
+    svg.text(svg.width / 2.0, MARGIN + CELL / 2.0, 14.0, caption);
+    let bottom = MARGIN + CAPTION + rows as f64 * CELL;
+    for (i, pile) in piles.iter().enumerate() {
+        let x = MARGIN + i as f64 * CELL;
+        for (level, c) in pile.iter().enumerate() {
+            let y = bottom - (level as f64 + 1.0) * CELL;
+            svg.rect(x, y, CELL, CELL, "#d9b47a", None);
+            svg.text(x + CELL / 2.0, y + CELL * 0.7, 14.0, &c.to_string());
+        }
+        svg.text(x + CELL / 2.0, bottom + CELL * 0.7, 12.0, &(i + 1).to_string());
+    }
+    svg.finish()
+}
+
+/// The crate stacks before the first move and after each of them, all frames
+/// sharing the same size. `sticky` selects the part 2 crane.
+pub fn ship_frames(ship: &Ship, sticky: bool) -> Vec<String> {
+    let states = ship.states(sticky);
+    let commands = ship.commands();
+    let rows = states
+        .iter()
+        .flat_map(|piles| piles.iter().map(Vec::len))
+        .max()
+        .unwrap_or(0);
+    states
+        .iter()
+        .enumerate()
+        .map(|(i, piles)| {
+            let caption = match i {
+                0 => String::from("start"),
+                _ => {
+                    let (from, to, n) = commands[i - 1];
+                    format!("{}/{}: move {} from {} to {}", i, commands.len(), n, from, to)
+                }
+            };
+            render_piles(piles, rows, &caption)
+        })
+        .collect()
+}
+
+/// An icicle chart of the directory tree: each directory spans the width of
+/// its parent in proportion to its size, one row per depth.
+pub fn file_system(fs: &FileSystem) -> String {
+    const WIDTH: f64 = 960.0;
+    let sizes = fs.directory_sizes();
+    let mut children = BTreeMap::<&str, Vec<&str>>::new();
+    for path in sizes.keys().filter(|p| *p != "/") {
+        let parent = &path[..=path[..path.len() - 1].rfind('/').unwrap()];
+        children.entry(parent).or_default().push(path);
+    }
+    let depth = sizes.keys().map(|p| p.matches('/').count()).max().unwrap_or(1);
+
+    let mut svg = Svg::new(
+        WIDTH + 2.0 * MARGIN,
+        2.0 * MARGIN + CAPTION + depth as f64 * CELL,
+    );
+    let total = sizes.get("/").copied().unwrap_or(0);
+    svg.text(svg.width / 2.0, MARGIN + CELL / 2.0, 14.0, &format!("/ uses {}", total));
+
+    let mut stack = vec![("/", MARGIN, WIDTH, 0usize)];
+    while let Some((path, x, width, level)) = stack.pop() {
+        let size = sizes[path];
+        if width < 0.5 {
+            continue;
+        }
+        let y = MARGIN + CAPTION + level as f64 * CELL;
+        let fill = shade((70, 130, 180), 0.3 + 0.7 * (level + 1) as f64 / depth as f64);
+        svg.rect(x, y, width, CELL, &fill, Some(&format!("{} {}", path, size)));
+        let name = path[..path.len() - 1].rsplit('/').next().unwrap_or("");
+        let label = if name.is_empty() { "/" } else { name };
+        if width > 8.0 * label.len() as f64 {
+            svg.text(x + width / 2.0, y + CELL * 0.7, 12.0, label);
+        }
+        let mut cx = x;
+        for child in children.get(path).into_iter().flatten() {
+            let w = match size {
+                0 => 0.0,
+                _ => width * sizes[*child] as f64 / size as f64,
+            };
+            stack.push((child, cx, w, level + 1));
+            cx += w;
+        }
+    }
+    svg.finish()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForestView {
+    /// Trees visible from outside in green, hidden ones in grey, darker when
+    /// taller.
+    Visibility,
+    /// Trees shaded by scenic score, the best one outlined.
+    ScenicScore,
+}
+
+pub fn forest(forest: &Forest, view: ForestView) -> String {
+    let (width, height) = (forest.width(), forest.height());
+    let cell = (640.0 / width.max(height).max(1) as f64).clamp(4.0, CELL);
+    let mut svg = Svg::new(
+        2.0 * MARGIN + width as f64 * cell,
+        2.0 * MARGIN + CAPTION + height as f64 * cell,
+    );
+    let distances = forest.viewing_distances();
+    let scores = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| ((x, y), distances.scenic_score(x, y)))
+        .collect::<Vec<_>>();
+    let best = scores.iter().max_by_key(|(_, s)| *s).copied();
+    let max_score = best.map_or(0, |(_, s)| s);
+
+    let caption = match view {
+        ForestView::Visibility => {
+            format!("{} visible trees", distances.visible_count())
+        }
+        ForestView::ScenicScore => format!("best scenic score {}", max_score),
+    };
+    svg.text(svg.width / 2.0, MARGIN + CELL / 2.0, 14.0, &caption);
+
+    for &((x, y), score) in scores.iter() {
+        let tree = forest.tree(x, y);
+        let visible = distances.is_visible(x, y);
+        let fill = match view {
+            ForestView::Visibility if visible => shade((34, 139, 34), 0.2 + tree as f64 / 11.0),
+            ForestView::Visibility => shade((96, 96, 96), 0.1 + tree as f64 / 14.0),
+            ForestView::ScenicScore if max_score == 0 => shade((200, 30, 30), 0.0),
+            ForestView::ScenicScore => shade((200, 30, 30), score as f64 / max_score as f64),
+        };
+        let title = format!(
+            "({}, {}) height {}, {}, scenic score {}",
+            x,
+            y,
+            tree,
+            if visible { "visible" } else { "hidden" },
+            score
+        );
+        let (px, py) = (MARGIN + x as f64 * cell, MARGIN + CAPTION + y as f64 * cell);
+        svg.rect(px, py, cell, cell, &fill, Some(&title));
+    }
+    if let (ForestView::ScenicScore, Some(((x, y), _))) = (view, best) {
+        write!(
+            svg.body,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="black" stroke-width="2"/>"#,
+            MARGIN + x as f64 * cell,
+            MARGIN + CAPTION + y as f64 * cell,
+            cell,
+            cell
+        )
+        .unwrap();
+        svg.body.push('\n');
+    }
+    svg.finish()
+}
+
+const MARKER_COLORS: [(u8, u8, u8); 3] = [(255, 165, 0), (65, 105, 225), (46, 139, 87)];
+
+/// The whole signal, wrapped every `columns` characters, with the first
+/// marker of each length highlighted.
+pub fn markers(signal: &str, lengths: &[usize], columns: usize) -> String {
+    let chars = signal.chars().collect::<Vec<_>>();
+    let columns = columns.max(1);
+    let rows = chars.len().div_ceil(columns).max(1);
+    let cell = CELL * 0.6;
+    let mut svg = Svg::new(
+        2.0 * MARGIN + columns.min(chars.len().max(1)) as f64 * cell,
+        2.0 * MARGIN + CAPTION * (1 + lengths.len()) as f64 + rows as f64 * cell,
+    );
+
+    let mut fills = vec![None; chars.len()];
+    for (i, &length) in lengths.iter().enumerate() {
+        let position = day6::marker_position(signal, length);
+        let color = MARKER_COLORS[i % MARKER_COLORS.len()];
+        let caption = match position {
+            0 => format!("no marker of {}", length),
+            p => format!("marker of {} ends at {}", length, p),
+        };
+        svg.rect(MARGIN, MARGIN + CAPTION * i as f64 + 4.0, 12.0, 12.0, &shade(color, 0.6), None);
+        writeln!(
+            svg.body,
+            r#"<text x="{}" y="{}" font-size="14">{}</text>"#,
+            MARGIN + 20.0,
+            MARGIN + CAPTION * i as f64 + 15.0,
+            escape(&caption)
+        )
+        .unwrap();
+        if position > 0 {
+            for fill in fills[position - length..position].iter_mut() {
+                fill.get_or_insert(shade(color, 0.6));
+            }
+        }
+    }
+
+    let top = MARGIN + CAPTION * lengths.len() as f64;
+    for (i, c) in chars.iter().enumerate() {
+        let (x, y) = (
+            MARGIN + (i % columns) as f64 * cell,
+            top + (i / columns) as f64 * cell,
+        );
+        if let Some(fill) = &fills[i] {
+            svg.rect(x, y, cell, cell, fill, None);
+        }
+        svg.text(x + cell / 2.0, y + cell * 0.75, 11.0, &c.to_string());
+    }
+    svg.finish()
+}
+
+/// The `length` characters wide window sliding over the signal until the
+/// first marker, with repeated characters in red and the marker in green.
+pub fn marker_frames(signal: &str, length: usize) -> Vec<String> {
+    const STRIP: usize = 48;
+    let chars = signal.chars().collect::<Vec<_>>();
+    if length == 0 || chars.len() < length {
+        return vec![];
+    }
+    let last = match day6::marker_position(signal, length) {
+        0 => chars.len() - length,
+        p => p - length,
+    };
+    (0..=last)
+        .map(|start| {
+            let window = &chars[start..start + length];
+            let is_marker = window
+                .iter()
+                .enumerate()
+                .all(|(i, c)| !window[i + 1..].contains(c));
+            let from = start.saturating_sub(STRIP / 2 - length.min(STRIP / 2) / 2);
+            let to = (from + STRIP).min(chars.len());
+            let mut svg = Svg::new(2.0 * MARGIN + STRIP as f64 * CELL, 2.0 * MARGIN + CAPTION + CELL);
+            let caption = match is_marker {
+                true => format!("marker of {} found after {} characters", length, start + length),
+                false => format!("{} characters processed", start + length),
+            };
+            svg.text(svg.width / 2.0, MARGIN + CELL / 2.0, 14.0, &caption);
+            for (i, c) in chars.iter().enumerate().take(to).skip(from) {
+                let x = MARGIN + (i - from) as f64 * CELL;
+                let y = MARGIN + CAPTION;
+                if (start..start + length).contains(&i) {
+                    let repeated = window.iter().filter(|w| *w == c).count() > 1;
+                    let fill = match (is_marker, repeated) {
+                        (true, _) => shade(MARKER_COLORS[2], 0.6),
+                        (false, true) => shade((220, 20, 60), 0.6),
+                        (false, false) => shade(MARKER_COLORS[0], 0.6),
+                    };
+                    svg.rect(x, y, CELL, CELL, &fill, None);
+                }
+                svg.text(x + CELL / 2.0, y + CELL * 0.7, 14.0, &c.to_string());
+            }
+            svg.finish()
+        })
+        .collect()
+}
+
+/// Writes `frames` as `dir/<name>-0000.svg`, `dir/<name>-0001.svg`, ...
+pub fn write_frames(dir: &Path, name: &str, frames: &[String]) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let digits = frames.len().saturating_sub(1).to_string().len().max(4);
+    let mut paths = vec![];
+    for (i, frame) in frames.iter().enumerate() {
+        let path = dir.join(format!("{}-{:0width$}.svg", name, i, width = digits));
+        fs::write(&path, frame)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day5::Day5, day7::Day7, solution::Solution};
+
+    #[test]
+    fn test_shade() {
+        assert_eq!(shade((0, 0, 0), 0.0), "#ffffff");
+        assert_eq!(shade((10, 20, 30), 1.0), "#0a141e");
+    }
+
+    #[test]
+    fn test_ship_frames() {
+        let ship = Day5::parse(
+            "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+             move 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2",
+        )
+        .unwrap();
+        let frames = ship_frames(&ship, false);
+        assert_eq!(frames.len(), 5);
+        assert!(frames[0].contains(">start<"));
+        assert!(frames[4].contains(">4/4: move 1 from 1 to 2<"));
+        // Every frame is as tall as the tallest pile ever gets
+        let header = |f: &str| f.lines().next().unwrap().to_string();
+        assert!(frames.iter().all(|f| header(f) == header(&frames[0])));
+        assert_eq!(frames[0].matches("<rect").count(), 1 + 6);
+    }
+
+    #[test]
+    fn test_file_system() {
+        let fs = Day7::parse(
+            "$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n$ cd a\n$ ls\ndir e\n\
+             29116 f\n2557 g\n62596 h.lst\n$ cd e\n$ ls\n584 i\n$ cd ..\n$ cd ..\n$ cd d\n$ ls\n\
+             4060174 j\n8033020 d.log\n5626152 d.ext\n7214296 k",
+        )
+        .unwrap();
+        let svg = file_system(&fs);
+        for title in ["/ 48381165", "/a/ 94853", "/d/ 24933642"] {
+            assert!(svg.contains(&format!("<title>{}</title>", title)), "{}", title);
+        }
+        // Too narrow to draw at this scale
+        assert!(!svg.contains("/a/e/"));
+    }
+
+    #[test]
+    fn test_forest() {
+        let forest = Forest::parse("30373\n25512\n65332\n33549\n35390").unwrap();
+        let svg = super::forest(&forest, ForestView::Visibility);
+        assert!(svg.contains(">21 visible trees<"));
+        assert_eq!(svg.matches("<title>").count(), 25);
+        let svg = super::forest(&forest, ForestView::ScenicScore);
+        assert!(svg.contains(">best scenic score 8<"));
+        assert!(svg.contains("(2, 3) height 5, visible, scenic score 8"));
+    }
+
+    #[test]
+    fn test_markers() {
+        let signal = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+        let svg = markers(signal, &[4, 14], 16);
+        assert!(svg.contains(">marker of 4 ends at 7<"));
+        assert!(svg.contains(">marker of 14 ends at 19<"));
+        let frames = marker_frames(signal, 4);
+        assert_eq!(frames.len(), 4);
+        assert!(frames[3].contains(">marker of 4 found after 7 characters<"));
+        assert!(marker_frames("aaaa", 4).len() == 1);
+        assert!(marker_frames("abc", 4).is_empty());
+    }
+
+    #[test]
+    fn test_write_frames() {
+        let dir = std::env::temp_dir().join(format!("aoc-frames-{}", std::process::id()));
+        let paths = write_frames(&dir, "day6", &[String::from("a"), String::from("b")]).unwrap();
+        assert_eq!(paths[1], dir.join("day6-0001.svg"));
+        assert_eq!(fs::read_to_string(&paths[1]).unwrap(), "b");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,44 @@
+//! Entry points for the browser playground, built with
+//! `cargo build --target wasm32-unknown-unknown --features wasm-bindgen`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::solution::{self, Part};
+
+/// Runs one part of a day on `input`, with errors rendered the way the CLI
+/// prints them.
+#[wasm_bindgen]
+pub fn solve(day: u8, part: u8, input: &str) -> Result<String, String> {
+    let solution = solution::get(day).ok_or_else(|| format!("day {} is not solved", day))?;
+    let part = Part::from_number(part)
+        .ok_or_else(|| format!("invalid part {}, expected 1 or 2", part))?;
+    solution
+        .run(part, input)
+        .map_err(|e| format!("day {}: {}", day, e))
+}
+
+/// The days `solve` accepts, in order.
+#[wasm_bindgen]
+pub fn days() -> Vec<u8> {
+    solution::registry().iter().map(|d| d.day()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(solve(6, 1, "mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Ok(String::from("7")));
+        assert_eq!(solve(6, 2, "mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Ok(String::from("19")));
+        assert_eq!(solve(6, 3, ""), Err(String::from("invalid part 3, expected 1 or 2")));
+        assert_eq!(solve(42, 1, ""), Err(String::from("day 42 is not solved")));
+        assert_eq!(
+            solve(4, 1, "2-4,6-8\n2-x,4-5"),
+            Err(String::from(
+                "day 4: line 2, column 3: cannot parse \"x\": invalid digit found in string"
+            ))
+        );
+        assert_eq!(days(), [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+}
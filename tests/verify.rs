@@ -0,0 +1,20 @@
+use std::path::Path;
+
+use advent_of_code_2022::{
+    run_all::Status,
+    verify::{self, Answers},
+};
+
+#[test]
+fn test_real_inputs_match_answers() {
+    let input_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("input/2022");
+    let answers = Answers::load(&input_dir.join("answers.toml")).unwrap();
+    let outcomes = verify::verify(&input_dir, &answers);
+    assert!(!outcomes.is_empty());
+    let failures = outcomes
+        .iter()
+        .filter(|o| o.status != Status::Ok)
+        .map(|o| o.to_string())
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
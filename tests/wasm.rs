@@ -0,0 +1,55 @@
+//! Runs the exported entry points under a headless wasm runtime. Needs the
+//! `wasm-bindgen-cli` matching the `wasm-bindgen` version in Cargo.lock:
+//!
+//! ```sh
+//! cargo test --target wasm32-unknown-unknown --features wasm-bindgen
+//! ```
+#![cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+
+use advent_of_code_2022::wasm::{days, solve};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const EXAMPLES: [(u8, &str, &str, &str); 8] = [
+    (1, "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n", "24000", "45000"),
+    (2, "A Y\nB X\nC Z\n", "15", "12"),
+    (
+        3,
+        "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\n\
+         wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\nttgJtRGJQctTZtZT\nCrZsJsPPZsGzwwsLwLmpwMDw\n",
+        "157",
+        "70",
+    ),
+    (4, "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8\n", "2", "4"),
+    (
+        5,
+        "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+         move 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2\n",
+        "CMZ",
+        "MCD",
+    ),
+    (6, "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n", "7", "19"),
+    (
+        7,
+        "$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n$ cd a\n$ ls\ndir e\n\
+         29116 f\n2557 g\n62596 h.lst\n$ cd e\n$ ls\n584 i\n$ cd ..\n$ cd ..\n$ cd d\n$ ls\n\
+         4060174 j\n8033020 d.log\n5626152 d.ext\n7214296 k\n",
+        "95437",
+        "24933642",
+    ),
+    (8, "30373\n25512\n65332\n33549\n35390\n", "21", "8"),
+];
+
+#[wasm_bindgen_test]
+fn solves_every_example() {
+    assert_eq!(days(), EXAMPLES.map(|(day, ..)| day));
+    for (day, input, part1, part2) in EXAMPLES {
+        assert_eq!(solve(day, 1, input).as_deref(), Ok(part1), "day {}", day);
+        assert_eq!(solve(day, 2, input).as_deref(), Ok(part2), "day {}", day);
+    }
+}
+
+#[wasm_bindgen_test]
+fn reports_errors() {
+    assert_eq!(solve(26, 1, ""), Err(String::from("day 26 is not solved")));
+    assert!(solve(2, 1, "A W").unwrap_err().starts_with("day 2: line 1, column 3:"));
+}